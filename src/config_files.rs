@@ -0,0 +1,386 @@
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The merged YAML of a config file, its includes and drop-ins, before deserializing
+#[derive(Debug)]
+pub struct LayeredConfig {
+    pub value: Value,
+    pub report: LoadReport,
+}
+
+/// What went into a config, for `-v`
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    /// Every file that contributed, in merge order
+    pub files: Vec<PathBuf>,
+    /// `extends` chains of profiles, e.g. `Work → wayland → base`
+    pub inheritance: Vec<String>,
+}
+
+/// `brolaunch.d` next to the config file, e.g. `~/.config/brolaunch.d`
+pub fn drop_in_dir(config_path: &Path) -> PathBuf {
+    config_path.parent().unwrap_or(Path::new(".")).join("brolaunch.d")
+}
+
+/// `*.yaml`/`*.yml` files of a drop-in directory in lexical order
+pub fn drop_in_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Loads `path` with its includes, merges the drop-in directory on top and resolves `extends`
+pub fn load(path: &Path) -> Result<LayeredConfig, Box<dyn std::error::Error>> {
+    let mut layered = LayeredConfig {
+        value: Value::Mapping(Mapping::new()),
+        report: LoadReport::default(),
+    };
+    load_file(path, &mut Vec::new(), &mut layered)?;
+    for drop_in in drop_in_files(&drop_in_dir(path)) {
+        load_file(&drop_in, &mut Vec::new(), &mut layered)?;
+    }
+    layered.report.inheritance = resolve_extends(&mut layered.value)?;
+    Ok(layered)
+}
+
+// Included files form the base the including file is merged onto, so a
+// personal config can include a shared one and override parts of it
+fn load_file(path: &Path, stack: &mut Vec<PathBuf>, layered: &mut LayeredConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let canonical = fs::canonicalize(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if stack.contains(&canonical) {
+        return Err(format!("include cycle: {} includes itself", path.display()).into());
+    }
+
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut value: Value = serde_yaml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if value.is_null() {
+        value = Value::Mapping(Mapping::new());
+    }
+    let Value::Mapping(mapping) = &mut value else {
+        return Err(format!("{}: expected a mapping at the top level", path.display()).into());
+    };
+
+    let includes = match mapping.remove("include") {
+        None => Vec::new(),
+        Some(Value::String(include)) => vec![include],
+        Some(Value::Sequence(includes)) => includes
+            .into_iter()
+            .map(|include| match include {
+                Value::String(include) => Ok(include),
+                _ => Err(format!("{}: include entries must be paths", path.display())),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(format!("{}: include must be a path or a list of paths", path.display()).into()),
+    };
+
+    stack.push(canonical);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    for include in includes {
+        let include = crate::expand::expand(&include).map_err(|e| format!("{}: include: {}", path.display(), e))?;
+        load_file(&base_dir.join(include), stack, layered)?;
+    }
+    stack.pop();
+
+    merge(&mut layered.value, value, &[]);
+    layered.report.files.push(path.to_path_buf());
+    Ok(())
+}
+
+// Profile lists that accumulate across files; argv-like and set-like lists
+// (wrapper, proxy.bypass, when.days, ...) are replaced like scalars
+const APPENDED_PROFILE_LISTS: &[&str] = &["patterns", "app_patterns", "private_patterns", "cli_flags"];
+
+fn appends_lists(path: &[String]) -> bool {
+    match path {
+        [top] => top == "unwrap_rules",
+        [section, _, key] => (section == "profiles" || section == "templates") && APPENDED_PROFILE_LISTS.contains(&key.as_str()),
+        _ => false,
+    }
+}
+
+/// Merges `overlay` into `base`: mappings key by key, the pattern lists and `cli_flags`
+/// of profiles and templates and the top-level `unwrap_rules` appended, everything else replaced
+pub fn merge(base: &mut Value, overlay: Value, path: &[String]) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                let mut child_path = path.to_vec();
+                child_path.push(key.as_str().unwrap_or_default().to_string());
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value, &child_path),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(overlay)) if appends_lists(path) => base.extend(overlay),
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Section {
+    Template,
+    Profile,
+}
+
+struct Inheritance {
+    templates: Mapping,
+    profiles: Mapping,
+    resolved: HashMap<(Section, String), Value>,
+}
+
+impl Inheritance {
+    fn raw(&self, section: Section, name: &str) -> Option<&Value> {
+        match section {
+            Section::Template => self.templates.get(name),
+            Section::Profile => self.profiles.get(name),
+        }
+    }
+
+    // A parent name refers to a template first, then to a profile
+    fn find_parent(&self, name: &str) -> Option<Section> {
+        [Section::Template, Section::Profile]
+            .into_iter()
+            .find(|section| self.raw(*section, name).is_some())
+    }
+
+    fn resolve(&mut self, section: Section, name: &str, chain: &mut Vec<(Section, String)>) -> Result<Value, String> {
+        let node = (section, name.to_string());
+        if let Some(resolved) = self.resolved.get(&node) {
+            return Ok(resolved.clone());
+        }
+        if chain.contains(&node) {
+            chain.push(node);
+            let names: Vec<&str> = chain.iter().map(|(_, name)| name.as_str()).collect();
+            return Err(format!("extends cycle: {}", names.join(" → ")));
+        }
+        let raw = self.raw(section, name).cloned().unwrap_or(Value::Null);
+
+        let resolved = match raw.get("extends") {
+            None => raw,
+            Some(Value::String(parent)) => {
+                let parent = parent.clone();
+                let parent_section = self
+                    .find_parent(&parent)
+                    .ok_or_else(|| format!("'{}' extends unknown profile or template '{}'", name, parent))?;
+                chain.push(node.clone());
+                let mut resolved = self.resolve(parent_section, &parent, chain)?;
+                chain.pop();
+                // Merged as a profile body, so only its pattern lists and cli_flags append
+                merge(&mut resolved, raw, &["profiles".to_string(), name.to_string()]);
+                resolved
+            }
+            Some(_) => return Err(format!("'{}': extends must be a profile or template name", name)),
+        };
+        self.resolved.insert(node, resolved.clone());
+        Ok(resolved)
+    }
+
+    // Only called after `resolve` succeeded, so the chain is known to end
+    fn chain_of(&self, section: Section, name: &str) -> Vec<String> {
+        let mut chain = vec![name.to_string()];
+        let mut current = self.raw(section, name);
+        while let Some(Value::String(parent)) = current.and_then(|value| value.get("extends")) {
+            chain.push(parent.clone());
+            current = self.find_parent(parent).and_then(|section| self.raw(section, parent));
+        }
+        chain
+    }
+}
+
+/// Applies `extends` to every profile (parents first, the profile's own keys on top,
+/// pattern lists and cli_flags appended) and removes the non-launchable `templates` section.
+/// Returns the inheritance chains for `-v`.
+pub fn resolve_extends(value: &mut Value) -> Result<Vec<String>, String> {
+    let Value::Mapping(config) = value else {
+        return Ok(Vec::new());
+    };
+    let templates = match config.remove("templates") {
+        Some(Value::Mapping(templates)) => templates,
+        None | Some(Value::Null) => Mapping::new(),
+        Some(_) => return Err("templates must be a mapping of template names to profile settings".to_string()),
+    };
+    let Some(Value::Mapping(profiles)) = config.get("profiles") else {
+        return Ok(Vec::new());
+    };
+
+    let mut inheritance = Inheritance {
+        templates,
+        profiles: profiles.clone(),
+        resolved: HashMap::new(),
+    };
+    let mut resolved_profiles = Mapping::new();
+    let mut chains = Vec::new();
+    for (key, raw) in profiles {
+        let name = key.as_str().unwrap_or_default();
+        if raw.get("extends").is_some() {
+            resolved_profiles.insert(key.clone(), inheritance.resolve(Section::Profile, name, &mut Vec::new())?);
+            chains.push(inheritance.chain_of(Section::Profile, name).join(" → "));
+        } else {
+            resolved_profiles.insert(key.clone(), raw.clone());
+        }
+    }
+    config.insert(Value::from("profiles"), Value::Mapping(resolved_profiles));
+    Ok(chains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("brolaunch-config-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_merge_appends_profile_lists_and_overrides_scalars() {
+        let mut base: Value = serde_yaml::from_str(
+            "browser_type: chromium\nchooser: {backends: [rofi]}\nprofiles:\n  Work:\n    user_data_dir: /a\n    patterns: [one]\n",
+        )
+        .unwrap();
+        let overlay: Value = serde_yaml::from_str(
+            "browser_type: firefox\nchooser: {backends: [fuzzel]}\nprofiles:\n  Work:\n    user_data_dir: /b\n    patterns: [two]\n  Home: {}\n",
+        )
+        .unwrap();
+        merge(&mut base, overlay, &[]);
+
+        assert_eq!(base["browser_type"], "firefox");
+        assert_eq!(base["chooser"]["backends"], serde_yaml::from_str::<Value>("[fuzzel]").unwrap());
+        assert_eq!(base["profiles"]["Work"]["user_data_dir"], "/b");
+        assert_eq!(base["profiles"]["Work"]["patterns"], serde_yaml::from_str::<Value>("[one, two]").unwrap());
+        assert!(base["profiles"]["Home"].is_mapping());
+    }
+
+    #[test]
+    fn test_merge_replaces_other_lists() {
+        let mut base: Value = serde_yaml::from_str(
+            "profiles:\n  Work:\n    hyprland_window_rules: [a]\n    proxy: {bypass: [localhost]}\n    when: {days: [mon-fri]}\n",
+        )
+        .unwrap();
+        let overlay: Value = serde_yaml::from_str(
+            "profiles:\n  Work:\n    hyprland_window_rules: [b]\n    proxy: {bypass: [\"*.corp\"]}\n    when: {days: [sat]}\n",
+        )
+        .unwrap();
+        merge(&mut base, overlay, &[]);
+
+        let work = &base["profiles"]["Work"];
+        assert_eq!(work["hyprland_window_rules"], serde_yaml::from_str::<Value>("[b]").unwrap());
+        assert_eq!(work["proxy"]["bypass"], serde_yaml::from_str::<Value>("[\"*.corp\"]").unwrap());
+        assert_eq!(work["when"]["days"], serde_yaml::from_str::<Value>("[sat]").unwrap());
+    }
+
+    #[test]
+    fn test_load_includes_and_drop_ins_in_order() {
+        let dir = temp_dir("layers");
+        fs::write(dir.join("team.yaml"), "default_profile: Work\nprofiles:\n  Work:\n    patterns: [team]\n").unwrap();
+        fs::write(dir.join("brolaunch.yaml"), "include: team.yaml\ndefault_profile: Home\nprofiles:\n  Work:\n    patterns: [mine]\n").unwrap();
+        fs::create_dir_all(dir.join("brolaunch.d")).unwrap();
+        fs::write(dir.join("brolaunch.d").join("20-late.yaml"), "default_profile: Late\n").unwrap();
+        fs::write(dir.join("brolaunch.d").join("10-early.yaml"), "profiles:\n  Work:\n    patterns: [drop-in]\n").unwrap();
+
+        let layered = load(&dir.join("brolaunch.yaml")).unwrap();
+        let names: Vec<_> = layered.report.files.iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["team.yaml", "brolaunch.yaml", "10-early.yaml", "20-late.yaml"]);
+        assert_eq!(layered.value["default_profile"], "Late");
+        assert_eq!(
+            layered.value["profiles"]["Work"]["patterns"],
+            serde_yaml::from_str::<Value>("[team, mine, drop-in]").unwrap()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_drop_ins_replace_wrapper_and_merge_env() {
+        let dir = temp_dir("wrapper");
+        fs::write(dir.join("brolaunch.yaml"), "profiles:\n  Work:\n    wrapper: [firejail]\n    env: {LANG: C, TZ: UTC}\n").unwrap();
+        fs::create_dir_all(dir.join("brolaunch.d")).unwrap();
+        fs::write(dir.join("brolaunch.d").join("10-a.yaml"), "profiles:\n  Work:\n    wrapper: [bwrap, --unshare-net]\n    env: {TZ: Europe/Berlin}\n").unwrap();
+
+        let work = &load(&dir.join("brolaunch.yaml")).unwrap().value["profiles"]["Work"];
+        assert_eq!(work["wrapper"], serde_yaml::from_str::<Value>("[bwrap, --unshare-net]").unwrap());
+        assert_eq!(work["env"], serde_yaml::from_str::<Value>("{LANG: C, TZ: Europe/Berlin}").unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_extends_from_templates_and_profiles() {
+        let mut value: Value = serde_yaml::from_str(
+            r#"
+templates:
+  wayland:
+    cli_flags: ["--ozone-platform=wayland"]
+    wrapper: ["firejail", "--private"]
+    hyprland_workspace: "2"
+    when: {days: [mon-fri], time: "09:00-17:00"}
+  Chat:
+    extends: wayland
+    app_mode: true
+profiles:
+  Work:
+    extends: wayland
+    cli_flags: ["--enable-features=X"]
+  Client:
+    extends: Work
+    hyprland_workspace: "3"
+    wrapper: ["systemd-run", "--user", "--scope"]
+    when: {days: [sat]}
+  Chat:
+    extends: Chat
+  Home:
+    patterns: [home]
+"#,
+        )
+        .unwrap();
+        let chains = resolve_extends(&mut value).unwrap();
+
+        assert!(value.get("templates").is_none());
+        let client = &value["profiles"]["Client"];
+        assert_eq!(client["hyprland_workspace"], "3");
+        assert_eq!(
+            client["cli_flags"],
+            serde_yaml::from_str::<Value>(r#"["--ozone-platform=wayland", "--enable-features=X"]"#).unwrap()
+        );
+        // Other lists are overridden, not unioned, while maps still merge key by key
+        assert_eq!(client["when"]["days"], serde_yaml::from_str::<Value>("[sat]").unwrap());
+        assert_eq!(client["when"]["time"], "09:00-17:00");
+        assert_eq!(client["wrapper"], serde_yaml::from_str::<Value>("[systemd-run, --user, --scope]").unwrap());
+        assert!(chains.contains(&"Client → Work → wayland".to_string()));
+        // A profile may extend the template of the same name
+        assert_eq!(value["profiles"]["Chat"]["app_mode"], true);
+        assert_eq!(chains.len(), 3);
+    }
+
+    #[test]
+    fn test_extends_cycle_and_unknown_parent_are_errors() {
+        let mut cycle: Value =
+            serde_yaml::from_str("templates:\n  a: {extends: b}\n  b: {extends: a}\nprofiles:\n  Work: {extends: a}\n").unwrap();
+        assert!(resolve_extends(&mut cycle).unwrap_err().contains("extends cycle"));
+
+        let mut unknown: Value = serde_yaml::from_str("profiles:\n  Work: {extends: nope}\n").unwrap();
+        assert!(resolve_extends(&mut unknown).unwrap_err().contains("unknown profile or template 'nope'"));
+    }
+
+    #[test]
+    fn test_include_cycle_is_an_error() {
+        let dir = temp_dir("cycle");
+        fs::write(dir.join("a.yaml"), "include: b.yaml\n").unwrap();
+        fs::write(dir.join("b.yaml"), "include: [a.yaml]\n").unwrap();
+        assert!(load(&dir.join("a.yaml")).unwrap_err().to_string().contains("include cycle"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,16 +1,118 @@
+use crate::tui_chooser;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
-use std::process::Command;
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DialogType {
     Kdialog,
     Zenity,
     Wofi,
     Fuzzel,
-    Hyprpicker,
+    Rofi,
+    Dmenu,
+    Bemenu,
+    Tofi,
+    Walker,
+    /// dmenu-compatible program from the `chooser.custom` config
+    Custom,
     Terminal,
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChooserConfig {
+    /// Backends to try in order; replaces the per-desktop defaults
+    pub backends: Option<Vec<DialogType>>,
+    pub custom: Option<CustomChooser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomChooser {
+    pub command: String,
+    /// `{prompt}` is replaced with the chooser prompt
+    pub args: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum BackendOutcome {
+    Selected(String),
+    Cancelled,
+    /// Nobody answered within the chooser timeout
+    TimedOut,
+}
+
+/// `Err` means the backend could not run and the next one should be tried.
+type BackendResult = Result<BackendOutcome, String>;
+
+/// Waits for a chooser process, killing it once `timeout` passes. `Ok(None)` means it timed out.
+fn wait_for_chooser(mut child: Child, timeout: Option<Duration>) -> std::io::Result<Option<Output>> {
+    if let Some(timeout) = timeout {
+        let deadline = Instant::now() + timeout;
+        while child.try_wait()?.is_none() {
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+    child.wait_with_output().map(Some)
+}
+
+impl DialogType {
+    /// Program that has to be installed for this dialog type
+    pub fn program(&self) -> Option<&'static str> {
+        match self {
+            DialogType::Kdialog => Some("kdialog"),
+            DialogType::Zenity => Some("zenity"),
+            DialogType::Wofi => Some("wofi"),
+            DialogType::Fuzzel => Some("fuzzel"),
+            DialogType::Rofi => Some("rofi"),
+            DialogType::Dmenu => Some("dmenu"),
+            DialogType::Bemenu => Some("bemenu"),
+            DialogType::Tofi => Some("tofi"),
+            DialogType::Walker => Some("walker"),
+            DialogType::Custom | DialogType::Terminal => None,
+        }
+    }
+
+    /// Launchers that only run natively on Wayland
+    fn wayland_only(&self) -> bool {
+        matches!(self, DialogType::Wofi | DialogType::Fuzzel | DialogType::Tofi | DialogType::Walker)
+    }
+
+    fn is_available(&self, session_type: &SessionType) -> bool {
+        let Some(program) = self.program() else {
+            return true;
+        };
+        if self.wayland_only() && *session_type == SessionType::X11 {
+            return false;
+        }
+        if !DesktopEnvironment::command_exists(program) {
+            return false;
+        }
+        match self {
+            // Upstream rofi is X11-only; on Wayland we need the wayland fork
+            DialogType::Rofi if *session_type == SessionType::Wayland => rofi_supports_wayland(),
+            // dmenu is X11-only
+            DialogType::Dmenu => *session_type != SessionType::Wayland,
+            _ => true,
+        }
+    }
+}
+
+fn rofi_supports_wayland() -> bool {
+    Command::new("rofi")
+        .arg("-version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("wayland"))
+        .unwrap_or(false)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SessionType {
     Wayland,
@@ -22,19 +124,20 @@ pub enum SessionType {
 pub struct DesktopEnvironment {
     pub name: String,
     pub session_type: SessionType,
-    pub dialog_type: DialogType,
+    /// Dialog types to try in order until one starts
+    pub dialog_chain: Vec<DialogType>,
 }
 
 impl DesktopEnvironment {
-    pub fn detect() -> Self {
+    pub fn detect(chooser_config: &ChooserConfig) -> Self {
         let session_type = Self::detect_session_type();
         let desktop_name = Self::detect_desktop_name();
-        let dialog_type = Self::detect_best_dialog(&desktop_name, &session_type);
+        let dialog_chain = Self::dialog_chain(&desktop_name, &session_type, chooser_config);
         
         DesktopEnvironment {
             name: desktop_name,
             session_type,
-            dialog_type,
+            dialog_chain,
         }
     }
     
@@ -91,79 +194,64 @@ impl DesktopEnvironment {
         "unknown".to_string()
     }
     
-    fn detect_best_dialog(desktop_name: &str, session_type: &SessionType) -> DialogType {
-        // Check desktop-specific preferences first
-        match desktop_name {
-            name if name.contains("kde") || name.contains("plasma") => {
-                if Self::command_exists("kdialog") {
-                    return DialogType::Kdialog;
-                } else if Self::command_exists("zenity") {
-                    return DialogType::Zenity;
-                }
-            }
+    fn dialog_chain(desktop_name: &str, session_type: &SessionType, chooser_config: &ChooserConfig) -> Vec<DialogType> {
+        // Configured backends are taken as-is, only skipping ones that aren't installed
+        if let Some(backends) = &chooser_config.backends {
+            return backends
+                .iter()
+                .filter(|dialog| match dialog {
+                    DialogType::Custom => chooser_config
+                        .custom
+                        .as_ref()
+                        .is_some_and(|custom| Self::command_exists(&custom.command)),
+                    other => other.program().is_none_or(Self::command_exists),
+                })
+                .cloned()
+                .collect();
+        }
+
+        let mut chain: Vec<DialogType> = Self::preferred_dialogs(desktop_name, session_type)
+            .into_iter()
+            .filter(|dialog| dialog.is_available(session_type))
+            .collect();
+        // A configured custom chooser is preferred over the per-desktop defaults
+        if chooser_config.custom.as_ref().is_some_and(|custom| Self::command_exists(&custom.command)) {
+            chain.insert(0, DialogType::Custom);
+        }
+        chain
+    }
+
+    /// Dialog types worth trying on this desktop, best first
+    fn preferred_dialogs(desktop_name: &str, session_type: &SessionType) -> Vec<DialogType> {
+        use DialogType::*;
+
+        let wayland = *session_type == SessionType::Wayland;
+        let mut preferences = match desktop_name {
+            name if name.contains("kde") || name.contains("plasma") => vec![Kdialog, Zenity],
             name if name.contains("gnome") || name.contains("unity") || name.contains("cinnamon") => {
-                if Self::command_exists("zenity") {
-                    return DialogType::Zenity;
-                } else if Self::command_exists("kdialog") {
-                    return DialogType::Kdialog;
-                }
+                vec![Zenity, Kdialog]
             }
-            name if name.contains("hyprland") => {
-                if *session_type == SessionType::Wayland {
-                    if Self::command_exists("hyprpicker") {
-                        return DialogType::Hyprpicker;
-                    } else if Self::command_exists("wofi") {
-                        return DialogType::Wofi;
-                    } else if Self::command_exists("fuzzel") {
-                        return DialogType::Fuzzel;
-                    } else if Self::command_exists("zenity") {
-                        return DialogType::Zenity; // via XWayland
-                    }
-                } else if Self::command_exists("zenity") {
-                    return DialogType::Zenity;
-                } else if Self::command_exists("kdialog") {
-                    return DialogType::Kdialog;
-                }
+            name if name.contains("hyprland") && wayland => vec![Walker, Tofi, Fuzzel, Wofi, Rofi, Bemenu],
+            name if (name.contains("sway") || name.contains("river")) && wayland => {
+                vec![Fuzzel, Wofi, Tofi, Bemenu, Rofi]
             }
-            name if name.contains("sway") || name.contains("river") => {
-                if *session_type == SessionType::Wayland {
-                    if Self::command_exists("wofi") {
-                        return DialogType::Wofi;
-                    } else if Self::command_exists("fuzzel") {
-                        return DialogType::Fuzzel;
-                    } else if Self::command_exists("zenity") {
-                        return DialogType::Zenity; // via XWayland
-                    }
-                } else if Self::command_exists("zenity") {
-                    return DialogType::Zenity;
-                } else if Self::command_exists("kdialog") {
-                    return DialogType::Kdialog;
-                }
+            name if ["i3", "bspwm", "dwm", "awesome", "xmonad", "qtile"].iter().any(|wm| name.contains(wm)) => {
+                vec![Rofi, Dmenu, Bemenu]
             }
-            _ => {}
-        }
-        
-        // Generic detection based on available tools and session type
-        if *session_type == SessionType::Wayland {
-            if Self::command_exists("wofi") {
-                return DialogType::Wofi;
-            } else if Self::command_exists("fuzzel") {
-                return DialogType::Fuzzel;
-            }
-        }
-        
+            _ if wayland => vec![Fuzzel, Wofi, Tofi, Bemenu, Walker, Rofi],
+            _ => vec![],
+        };
+
         // Universal fallbacks
-        if Self::command_exists("zenity") {
-            DialogType::Zenity
-        } else if Self::command_exists("kdialog") {
-            DialogType::Kdialog
-        } else {
-            // Final fallback to terminal-based selection
-            DialogType::Terminal
+        for dialog in [Zenity, Kdialog, Rofi, Dmenu, Bemenu, Terminal] {
+            if !preferences.contains(&dialog) {
+                preferences.push(dialog);
+            }
         }
+        preferences
     }
     
-    fn command_exists(command: &str) -> bool {
+    pub fn command_exists(command: &str) -> bool {
         Command::new("which")
             .arg(command)
             .output()
@@ -172,85 +260,280 @@ impl DesktopEnvironment {
     }
 }
 
-pub fn show_profile_chooser_with_debug(profiles: &[String], verbose: bool) -> Option<String> {
-    let desktop_env = DesktopEnvironment::detect();
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpenMode {
+    /// Whatever the command line / profile asked for
+    Default,
+    App,
+    Private,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChooserSelection {
+    Open {
+        profile: String,
+        mode: OpenMode,
+        /// Persist "always open this domain in this profile"
+        remember: bool,
+    },
+    CopyUrlAndCancel,
+    /// The chooser was closed after `chooser_timeout` without an answer
+    TimedOut,
+}
+
+fn build_choices(profiles: &[String], domain: Option<&str>) -> Vec<(String, ChooserSelection)> {
+    let open = |profile: &String, mode: OpenMode, remember: bool| ChooserSelection::Open {
+        profile: profile.clone(),
+        mode,
+        remember,
+    };
+
+    let mut choices: Vec<(String, ChooserSelection)> = profiles
+        .iter()
+        .map(|profile| (profile.clone(), open(profile, OpenMode::Default, false)))
+        .collect();
+
+    for profile in profiles {
+        choices.push((format!("{} — open as app", profile), open(profile, OpenMode::App, false)));
+        choices.push((format!("{} — private window", profile), open(profile, OpenMode::Private, false)));
+    }
+
+    if let Some(domain) = domain {
+        for profile in profiles {
+            choices.push((format!("Always open {} in {}", domain, profile), open(profile, OpenMode::Default, true)));
+        }
+    }
+
+    choices.push(("Copy URL and cancel".to_string(), ChooserSelection::CopyUrlAndCancel));
+    choices
+}
+
+fn chooser_prompt(url: &str) -> String {
+    const MAX_URL_LEN: usize = 80;
+    if url.chars().count() > MAX_URL_LEN {
+        let truncated: String = url.chars().take(MAX_URL_LEN - 1).collect();
+        format!("Open {}… with:", truncated)
+    } else {
+        format!("Open {} with:", url)
+    }
+}
+
+/// `previews` maps profile names to detail lines shown by choosers that support it.
+pub fn show_profile_chooser_with_debug(
+    profiles: &[String],
+    previews: &HashMap<String, Vec<String>>,
+    url: &str,
+    domain: Option<&str>,
+    chooser_config: &ChooserConfig,
+    timeout: Option<Duration>,
+    verbose: bool,
+) -> Option<ChooserSelection> {
+    let desktop_env = DesktopEnvironment::detect(chooser_config);
     
     if verbose {
         println!("🖥️  Desktop: {} ({:?})", desktop_env.name, desktop_env.session_type);
-        println!("💬 Dialog system: {:?}", desktop_env.dialog_type);
+        println!("💬 Dialog chain: {:?}", desktop_env.dialog_chain);
     }
-    
-    match desktop_env.dialog_type {
-        DialogType::Kdialog => show_kdialog_chooser(profiles),
-        DialogType::Zenity => show_zenity_chooser(profiles),
-        DialogType::Wofi => show_wofi_chooser(profiles),
-        DialogType::Fuzzel => show_fuzzel_chooser(profiles),
-        DialogType::Hyprpicker => show_hyprpicker_chooser(profiles),
-        DialogType::Terminal => show_terminal_chooser(profiles),
+
+    let choices = build_choices(profiles, domain);
+    let labels: Vec<String> = choices.iter().map(|(label, _)| label.clone()).collect();
+    let prompt = chooser_prompt(url);
+    let label_previews: Vec<Vec<String>> = choices
+        .iter()
+        .map(|(_, choice)| match choice {
+            ChooserSelection::Open { profile, .. } => previews.get(profile).cloned().unwrap_or_default(),
+            ChooserSelection::CopyUrlAndCancel => vec![format!("Copy {} to the clipboard without opening it", url)],
+            ChooserSelection::TimedOut => vec![],
+        })
+        .collect();
+
+    let mut selection = None;
+    for dialog_type in &desktop_env.dialog_chain {
+        let result = match dialog_type {
+            DialogType::Kdialog => show_kdialog_chooser(&labels, &prompt, timeout),
+            DialogType::Zenity => show_zenity_chooser(&labels, &prompt, timeout),
+            DialogType::Terminal => show_terminal_chooser(&labels, &label_previews, &prompt, timeout),
+            DialogType::Custom => match &chooser_config.custom {
+                Some(custom) => show_custom_chooser(custom, &labels, &prompt, timeout),
+                None => Err("no chooser.custom command configured".to_string()),
+            },
+            dmenu_style => show_dmenu_chooser(dmenu_style, &labels, &prompt, timeout),
+        };
+        match result {
+            Ok(outcome) => {
+                selection = Some(outcome);
+                break;
+            }
+            Err(e) => {
+                if verbose {
+                    println!("⚠️  {:?} chooser failed ({}), trying next backend", dialog_type, e);
+                }
+            }
+        }
+    }
+
+    match selection? {
+        BackendOutcome::Selected(label) => choices
+            .into_iter()
+            .find(|(choice_label, _)| *choice_label == label)
+            .map(|(_, choice)| choice),
+        BackendOutcome::Cancelled => None,
+        BackendOutcome::TimedOut => Some(ChooserSelection::TimedOut),
+    }
+}
+
+/// Copies text to the clipboard using whichever clipboard tool is installed.
+pub fn copy_to_clipboard(text: &str) -> bool {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let candidates: [(&str, &[&str]); 3] = [
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+
+    for (program, args) in candidates {
+        if !DesktopEnvironment::command_exists(program) {
+            continue;
+        }
+        let Ok(mut child) = Command::new(program).args(args).stdin(Stdio::piped()).spawn() else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        if child.wait().map(|status| status.success()).unwrap_or(false) {
+            return true;
+        }
     }
+    false
 }
 
-fn show_kdialog_chooser(profiles: &[String]) -> Option<String> {
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn show_kdialog_chooser(profiles: &[String], prompt: &str, timeout: Option<Duration>) -> BackendResult {
     let mut cmd = Command::new("kdialog");
     cmd.arg("--title").arg("Select Profile");
-    cmd.arg("--menu").arg("Choose a browser profile:");
+    cmd.arg("--menu").arg(prompt);
     
     // Add numbered options for kdialog
     for (i, profile) in profiles.iter().enumerate() {
         cmd.arg(format!("{}", i + 1));
         cmd.arg(profile);
     }
+
+    let child = cmd
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start kdialog: {}", e))?;
     
-    match cmd.output() {
-        Ok(output) if output.status.success() => {
+    match wait_for_chooser(child, timeout) {
+        Ok(Some(output)) if output.status.success() => {
             let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if let Ok(index) = selection.parse::<usize>() {
-                if index > 0 && index <= profiles.len() {
-                    return Some(profiles[index - 1].clone());
-                }
+            if let Ok(index) = selection.parse::<usize>()
+                && index > 0 && index <= profiles.len()
+            {
+                return Ok(BackendOutcome::Selected(profiles[index - 1].clone()));
             }
-            None
+            Ok(BackendOutcome::Cancelled)
         }
-        _ => None,
+        // Exit code 1 is a cancelled dialog
+        Ok(Some(output)) if output.status.code() == Some(1) => Ok(BackendOutcome::Cancelled),
+        Ok(Some(output)) => Err(format!("kdialog exited with {}", output.status)),
+        Ok(None) => Ok(BackendOutcome::TimedOut),
+        Err(e) => Err(format!("failed to wait for kdialog: {}", e)),
     }
 }
 
-fn show_zenity_chooser(profiles: &[String]) -> Option<String> {
+fn show_zenity_chooser(profiles: &[String], prompt: &str, timeout: Option<Duration>) -> BackendResult {
     let mut cmd = Command::new("zenity");
     cmd.arg("--list");
     cmd.arg("--title=Select Profile");
-    cmd.arg("--text=Choose a browser profile:");
+    cmd.arg(format!("--text={}", escape_markup(prompt)));
     cmd.arg("--column=Profile");
     
     for profile in profiles {
         cmd.arg(profile);
     }
+
+    let child = cmd
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start zenity: {}", e))?;
     
-    match cmd.output() {
-        Ok(output) if output.status.success() => {
+    match wait_for_chooser(child, timeout) {
+        Ok(Some(output)) if output.status.success() => {
             let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
             if !selection.is_empty() && profiles.contains(&selection) {
-                Some(selection)
+                Ok(BackendOutcome::Selected(selection))
             } else {
-                None
+                Ok(BackendOutcome::Cancelled)
             }
         }
-        _ => None,
+        // Exit code 1 is a cancelled dialog
+        Ok(Some(output)) if output.status.code() == Some(1) => Ok(BackendOutcome::Cancelled),
+        Ok(Some(output)) => Err(format!("zenity exited with {}", output.status)),
+        Ok(None) => Ok(BackendOutcome::TimedOut),
+        Err(e) => Err(format!("failed to wait for zenity: {}", e)),
     }
 }
 
-fn show_wofi_chooser(profiles: &[String]) -> Option<String> {
-    let mut cmd = Command::new("wofi");
-    cmd.arg("--dmenu");
-    cmd.arg("--prompt=Select Profile:");
-    cmd.arg("--width=300");
-    cmd.arg("--height=200");
-    cmd.arg("--location=center");
+/// Arguments that put a launcher into dmenu mode (options on stdin, selection on stdout)
+fn dmenu_args(dialog_type: &DialogType, prompt: &str) -> Vec<String> {
+    let args: Vec<&str> = match dialog_type {
+        DialogType::Wofi => vec!["--dmenu", "--width=600", "--height=300", "--location=center", "--prompt"],
+        DialogType::Fuzzel => vec!["--dmenu", "--width=60", "--prompt"],
+        DialogType::Rofi => vec!["-dmenu", "-i", "-p"],
+        DialogType::Dmenu => vec!["-i", "-l", "15", "-p"],
+        DialogType::Bemenu => vec!["-i", "-l", "15", "-p"],
+        DialogType::Tofi => vec!["--prompt-text"],
+        DialogType::Walker => vec!["--dmenu", "--placeholder"],
+        _ => vec![],
+    };
+
+    let mut args: Vec<String> = args.into_iter().map(str::to_string).collect();
+    let prompt = match dialog_type {
+        // fuzzel and tofi render the prompt directly in front of the input
+        DialogType::Fuzzel | DialogType::Tofi => format!("{} ", prompt),
+        _ => prompt.to_string(),
+    };
+    args.push(prompt);
+    args
+}
+
+fn show_dmenu_chooser(dialog_type: &DialogType, profiles: &[String], prompt: &str, timeout: Option<Duration>) -> BackendResult {
+    let program = dialog_type
+        .program()
+        .ok_or_else(|| format!("{:?} is not a dmenu-style chooser", dialog_type))?;
+    run_dmenu_program(program, &dmenu_args(dialog_type, prompt), profiles, timeout)
+}
+
+fn show_custom_chooser(custom: &CustomChooser, profiles: &[String], prompt: &str, timeout: Option<Duration>) -> BackendResult {
+    let args: Vec<String> = custom
+        .args
+        .iter()
+        .flatten()
+        .map(|arg| arg.replace("{prompt}", prompt))
+        .collect();
+    run_dmenu_program(&custom.command, &args, profiles, timeout)
+}
+
+/// Exit code of dmenu, rofi, wofi, fuzzel, bemenu, tofi and walker when dismissed without a choice
+const DMENU_CANCEL_EXIT_CODE: i32 = 1;
+
+/// Runs a dmenu-compatible program: options on stdin, selection on stdout
+fn run_dmenu_program(program: &str, args: &[String], profiles: &[String], timeout: Option<Duration>) -> BackendResult {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
     
     let input = profiles.join("\n");
     
-    match cmd.stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
+    match cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
     {
         Ok(mut child) => {
@@ -262,106 +545,103 @@ fn show_wofi_chooser(profiles: &[String]) -> Option<String> {
                 });
             }
             
-            match child.wait_with_output() {
-                Ok(output) if output.status.success() => {
+            match wait_for_chooser(child, timeout) {
+                Ok(Some(output)) if output.status.success() => {
                     let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
                     if !selection.is_empty() && profiles.contains(&selection) {
-                        Some(selection)
+                        Ok(BackendOutcome::Selected(selection))
                     } else {
-                        None
+                        Ok(BackendOutcome::Cancelled)
                     }
                 }
-                _ => None,
+                // Some launchers (wofi, walker) print warnings even when the user just hit Esc,
+                // so only the exit code tells a cancel from a failure
+                Ok(Some(output)) if output.status.code() == Some(DMENU_CANCEL_EXIT_CODE) => Ok(BackendOutcome::Cancelled),
+                Ok(Some(output)) => Err(format!(
+                    "{} exited with {}: {}",
+                    program,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )),
+                Ok(None) => Ok(BackendOutcome::TimedOut),
+                Err(e) => Err(format!("failed to wait for {}: {}", program, e)),
             }
         }
-        _ => None,
+        Err(e) => Err(format!("failed to start {}: {}", program, e)),
     }
 }
 
-fn show_fuzzel_chooser(profiles: &[String]) -> Option<String> {
-    let mut cmd = Command::new("fuzzel");
-    cmd.arg("--dmenu");
-    cmd.arg("--prompt=Profile: ");
-    
-    let input = profiles.join("\n");
-    
-    match cmd.stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-    {
-        Ok(mut child) => {
-            if let Some(stdin) = child.stdin.take() {
-                use std::io::Write;
-                let _ = std::thread::spawn(move || {
-                    let mut stdin = stdin;
-                    let _ = stdin.write_all(input.as_bytes());
-                });
-            }
-            
-            match child.wait_with_output() {
-                Ok(output) if output.status.success() => {
-                    let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if !selection.is_empty() && profiles.contains(&selection) {
-                        Some(selection)
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            }
-        }
-        _ => None,
+fn show_terminal_chooser(
+    profiles: &[String],
+    previews: &[Vec<String>],
+    prompt: &str,
+    timeout: Option<Duration>,
+) -> BackendResult {
+    if tui_chooser::is_supported() {
+        return match tui_chooser::choose(prompt, profiles, previews, timeout) {
+            Ok(tui_chooser::Choice::Selected(index)) => Ok(BackendOutcome::Selected(profiles[index].clone())),
+            Ok(tui_chooser::Choice::Cancelled) => Ok(BackendOutcome::Cancelled),
+            Ok(tui_chooser::Choice::TimedOut) => Ok(BackendOutcome::TimedOut),
+            Err(e) => Err(format!("terminal UI failed: {}", e)),
+        };
     }
+    Ok(show_numbered_chooser(profiles, prompt, timeout))
 }
 
-fn show_hyprpicker_chooser(profiles: &[String]) -> Option<String> {
-    // Hyprpicker is primarily for color picking, but we can use it creatively
-    // For now, fall back to terminal chooser but with Hyprland-specific messaging
-    println!("\n🪟 Hyprland Profile Selector");
-    println!("Available profiles:");
+/// Plain numbered prompt for dumb terminals and non-interactive stdin
+fn show_numbered_chooser(profiles: &[String], prompt: &str, timeout: Option<Duration>) -> BackendOutcome {
+    use std::io::{self, BufRead, Write};
+    use std::sync::mpsc;
+
+    println!("\n{}", prompt);
     for (i, profile) in profiles.iter().enumerate() {
         println!("  {}: {}", i + 1, profile);
     }
 
-    print!("Select profile (1-{}): ", profiles.len());
-    use std::io::{self, Write};
-    let _ = io::stdout().flush();
-
-    let mut input = String::new();
-    match io::stdin().read_line(&mut input) {
-        Ok(_) => {
-            if let Ok(index) = input.trim().parse::<usize>() {
-                if index > 0 && index <= profiles.len() {
-                    return Some(profiles[index - 1].clone());
-                }
+    // Read stdin on a thread so an unanswered prompt can time out
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if sender.send(line).is_err() {
+                break;
             }
-            None
         }
-        _ => None,
-    }
-}
-
-fn show_terminal_chooser(profiles: &[String]) -> Option<String> {
-    println!("\nAvailable profiles:");
-    for (i, profile) in profiles.iter().enumerate() {
-        println!("  {}: {}", i + 1, profile);
-    }
+    });
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
 
-    print!("Select profile (1-{}): ", profiles.len());
-    use std::io::{self, Write};
-    let _ = io::stdout().flush();
+    loop {
+        print!("Select profile (1-{}, empty to cancel): ", profiles.len());
+        let _ = io::stdout().flush();
 
-    let mut input = String::new();
-    match io::stdin().read_line(&mut input) {
-        Ok(_) => {
-            if let Ok(index) = input.trim().parse::<usize>() {
-                if index > 0 && index <= profiles.len() {
-                    return Some(profiles[index - 1].clone());
+        let line = match deadline {
+            Some(deadline) => match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => line,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    println!();
+                    return BackendOutcome::TimedOut;
                 }
-            }
-            None
+                // EOF: nobody is there to answer
+                Err(mpsc::RecvTimeoutError::Disconnected) => return BackendOutcome::Cancelled,
+            },
+            None => match receiver.recv() {
+                Ok(line) => line,
+                Err(_) => return BackendOutcome::Cancelled,
+            },
+        };
+
+        let Ok(input) = line else {
+            return BackendOutcome::Cancelled;
+        };
+        let input = input.trim();
+        if input.is_empty() || input.eq_ignore_ascii_case("q") {
+            return BackendOutcome::Cancelled;
+        }
+        if let Ok(index) = input.parse::<usize>()
+            && index > 0 && index <= profiles.len()
+        {
+            return BackendOutcome::Selected(profiles[index - 1].clone());
         }
-        _ => None,
+        println!("Invalid selection '{}'", input);
     }
 }
 
@@ -378,10 +658,116 @@ mod tests {
     
     #[test]
     fn test_desktop_detection() {
-        let desktop_env = DesktopEnvironment::detect();
+        let desktop_env = DesktopEnvironment::detect(&ChooserConfig::default());
         assert!(!desktop_env.name.is_empty());
+        assert_eq!(desktop_env.dialog_chain.last(), Some(&DialogType::Terminal));
+    }
+
+    #[test]
+    fn test_configured_dialog_chain() {
+        let config: ChooserConfig =
+            serde_yaml::from_str("backends: [custom, rofi, terminal]\ncustom:\n  command: echo\n").unwrap();
+        let chain = DesktopEnvironment::dialog_chain("kde", &SessionType::X11, &config);
+        assert_eq!(chain.first(), Some(&DialogType::Custom));
+        assert_eq!(chain.last(), Some(&DialogType::Terminal));
+
+        assert!(serde_yaml::from_str::<ChooserConfig>("backends: [not_a_backend]").is_err());
+
+        // Without `backends`, a custom chooser goes in front of the defaults
+        let config: ChooserConfig = serde_yaml::from_str("custom:\n  command: echo\n").unwrap();
+        let chain = DesktopEnvironment::dialog_chain("kde", &SessionType::X11, &config);
+        assert_eq!(chain.first(), Some(&DialogType::Custom));
+        assert_eq!(chain.last(), Some(&DialogType::Terminal));
+    }
+
+    #[test]
+    fn test_custom_chooser_failure_is_an_error() {
+        let custom = CustomChooser { command: "nonexistent_command_12345".to_string(), args: None };
+        assert!(show_custom_chooser(&custom, &["Work".to_string()], "Open:", None).is_err());
+
+        // `head -n 1` behaves like a dmenu that picks the first entry
+        let custom = CustomChooser {
+            command: "head".to_string(),
+            args: Some(vec!["-n".to_string(), "1".to_string()]),
+        };
+        let profiles = vec!["Work".to_string(), "Personal".to_string()];
+        assert_eq!(
+            show_custom_chooser(&custom, &profiles, "Open:", None),
+            Ok(BackendOutcome::Selected("Work".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_custom_chooser_exit_codes() {
+        let sh = |script: &str| CustomChooser {
+            command: "sh".to_string(),
+            args: Some(vec!["-c".to_string(), script.to_string()]),
+        };
+        let profiles = vec!["Work".to_string()];
+        // Warnings on stderr do not turn a cancel into a failure
+        assert_eq!(
+            show_custom_chooser(&sh("echo 'warning: no icon theme' >&2; exit 1"), &profiles, "Open:", None),
+            Ok(BackendOutcome::Cancelled)
+        );
+        assert!(show_custom_chooser(&sh("exit 2"), &profiles, "Open:", None).is_err());
+    }
+
+    #[test]
+    fn test_custom_chooser_timeout() {
+        let custom = CustomChooser {
+            command: "sleep".to_string(),
+            args: Some(vec!["5".to_string()]),
+        };
+        let started = Instant::now();
+        assert_eq!(
+            show_custom_chooser(&custom, &["Work".to_string()], "Open:", Some(Duration::from_millis(100))),
+            Ok(BackendOutcome::TimedOut)
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
     }
     
+    #[test]
+    fn test_build_choices() {
+        let profiles = vec!["Work".to_string(), "Personal".to_string()];
+        let choices = build_choices(&profiles, Some("example.com"));
+        assert_eq!(choices.len(), 9);
+        assert_eq!(
+            choices[0].1,
+            ChooserSelection::Open { profile: "Work".to_string(), mode: OpenMode::Default, remember: false }
+        );
+        assert!(choices.iter().any(|(label, choice)| label == "Personal — private window"
+            && *choice == ChooserSelection::Open { profile: "Personal".to_string(), mode: OpenMode::Private, remember: false }));
+        assert!(choices.iter().any(|(label, choice)| label == "Always open example.com in Personal"
+            && matches!(choice, ChooserSelection::Open { remember: true, .. })));
+        assert_eq!(choices.last().unwrap().1, ChooserSelection::CopyUrlAndCancel);
+
+        assert_eq!(build_choices(&profiles, None).len(), 7);
+    }
+
+    #[test]
+    fn test_chooser_prompt_truncates_long_urls() {
+        assert_eq!(chooser_prompt("https://example.com"), "Open https://example.com with:");
+        let long_url = format!("https://example.com/{}", "a".repeat(200));
+        assert!(chooser_prompt(&long_url).chars().count() < 100);
+    }
+
+    #[test]
+    fn test_preferred_dialogs() {
+        let hyprland = DesktopEnvironment::preferred_dialogs("hyprland", &SessionType::Wayland);
+        assert_eq!(hyprland.first(), Some(&DialogType::Walker));
+        assert_eq!(hyprland.last(), Some(&DialogType::Terminal));
+
+        let i3 = DesktopEnvironment::preferred_dialogs("i3", &SessionType::X11);
+        assert_eq!(&i3[..2], &[DialogType::Rofi, DialogType::Dmenu]);
+        assert_eq!(i3.iter().filter(|d| **d == DialogType::Rofi).count(), 1);
+    }
+
+    #[test]
+    fn test_dmenu_args() {
+        assert_eq!(dmenu_args(&DialogType::Rofi, "Open:"), vec!["-dmenu", "-i", "-p", "Open:"]);
+        assert_eq!(dmenu_args(&DialogType::Tofi, "Open:"), vec!["--prompt-text", "Open: "]);
+    }
+
     #[test]
     fn test_command_exists() {
         // Test with a command that should exist on most systems
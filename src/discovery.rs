@@ -0,0 +1,225 @@
+use crate::sandbox::Sandbox;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A profile that already exists inside a browser's own profile store
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserProfile {
+    /// Name shown in the browser's profile picker
+    pub name: String,
+    /// Chromium: directory inside the user data dir (`Default`, `Profile 1`).
+    /// Firefox: absolute path of the profile directory.
+    pub directory: String,
+    pub is_default: bool,
+}
+
+#[derive(Deserialize)]
+struct LocalState {
+    profile: Option<LocalStateProfiles>,
+}
+
+#[derive(Deserialize)]
+struct LocalStateProfiles {
+    info_cache: Option<HashMap<String, LocalStateProfileInfo>>,
+    last_used: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LocalStateProfileInfo {
+    name: Option<String>,
+}
+
+/// Default user data directory of a Chromium-family browser, guessed from its binary
+/// name (or Flatpak app id / snap name) and placed inside the sandbox when there is one
+pub fn chromium_user_data_dir(command: &[String]) -> Option<PathBuf> {
+    let sandbox = Sandbox::from_command(command);
+    let binary_name = match &sandbox {
+        Some(Sandbox::Flatpak { app_id }) => app_id.to_lowercase(),
+        Some(Sandbox::Snap { name }) => name.to_lowercase(),
+        None => Path::new(command.first()?).file_name()?.to_string_lossy().to_lowercase(),
+    };
+    let relative = if binary_name.contains("google-chrome") || binary_name.contains("google.chrome") || binary_name == "chrome" {
+        match binary_name.as_str() {
+            name if name.ends_with("-beta") => "google-chrome-beta",
+            name if name.ends_with("-unstable") => "google-chrome-unstable",
+            _ => "google-chrome",
+        }
+    } else if binary_name.contains("brave") {
+        "BraveSoftware/Brave-Browser"
+    } else if binary_name.contains("vivaldi") {
+        "vivaldi"
+    } else if binary_name.contains("microsoft-edge") || binary_name.contains("microsoft.edge") || binary_name.contains("msedge") {
+        "microsoft-edge"
+    } else {
+        "chromium"
+    };
+    let config_dir = match &sandbox {
+        Some(sandbox) => sandbox.config_dir()?,
+        None => dirs::config_dir()?,
+    };
+    Some(config_dir.join(relative))
+}
+
+pub fn parse_local_state(contents: &str) -> Result<Vec<BrowserProfile>, serde_json::Error> {
+    let local_state: LocalState = serde_json::from_str(contents)?;
+    let Some(profiles) = local_state.profile else {
+        return Ok(Vec::new());
+    };
+    let last_used = profiles.last_used.unwrap_or_else(|| "Default".to_string());
+
+    let mut discovered: Vec<BrowserProfile> = profiles
+        .info_cache
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(directory, info)| BrowserProfile {
+            name: info.name.unwrap_or_else(|| directory.clone()),
+            is_default: directory == last_used,
+            directory,
+        })
+        .collect();
+    discovered.sort_by(|a, b| a.directory.cmp(&b.directory));
+    Ok(discovered)
+}
+
+/// Reads `<user_data_dir>/Local State`; a missing file means no profiles yet
+pub fn read_chromium_profiles(user_data_dir: &Path) -> Result<Vec<BrowserProfile>, Box<dyn std::error::Error>> {
+    let local_state = user_data_dir.join("Local State");
+    if !local_state.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(parse_local_state(&fs::read_to_string(local_state)?)?)
+}
+
+pub fn firefox_profiles_dir(command: &[String]) -> Option<PathBuf> {
+    match Sandbox::from_command(command) {
+        Some(sandbox) => sandbox.firefox_profiles_dir(),
+        None => Some(dirs::home_dir()?.join(".mozilla").join("firefox")),
+    }
+}
+
+/// Parses `profiles.ini`; relative profile paths are resolved against `base_dir`
+pub fn parse_profiles_ini(contents: &str, base_dir: &Path) -> Vec<BrowserProfile> {
+    let mut sections: Vec<HashMap<String, String>> = Vec::new();
+    let mut in_profile_section = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_profile_section = section.starts_with("Profile");
+            if in_profile_section {
+                sections.push(HashMap::new());
+            }
+            continue;
+        }
+        if in_profile_section
+            && let Some((key, value)) = line.split_once('=')
+            && let Some(section) = sections.last_mut()
+        {
+            section.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    sections
+        .into_iter()
+        .filter_map(|section| {
+            let name = section.get("Name")?.clone();
+            let path = section.get("Path")?;
+            let directory = if section.get("IsRelative").map(String::as_str) == Some("0") {
+                PathBuf::from(path)
+            } else {
+                base_dir.join(path)
+            };
+            Some(BrowserProfile {
+                name,
+                directory: directory.to_string_lossy().into_owned(),
+                is_default: section.get("Default").map(String::as_str) == Some("1"),
+            })
+        })
+        .collect()
+}
+
+pub fn read_firefox_profiles(profiles_dir: &Path) -> Result<Vec<BrowserProfile>, Box<dyn std::error::Error>> {
+    let profiles_ini = profiles_dir.join("profiles.ini");
+    if !profiles_ini.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(parse_profiles_ini(&fs::read_to_string(profiles_ini)?, profiles_dir))
+}
+
+/// Finds a profile by display name (case-insensitive) or by its directory name
+pub fn find_profile<'a>(profiles: &'a [BrowserProfile], reference: &str) -> Option<&'a BrowserProfile> {
+    profiles
+        .iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(reference))
+        .or_else(|| {
+            profiles.iter().find(|profile| {
+                profile.directory == reference
+                    || Path::new(&profile.directory).file_name().is_some_and(|name| name == reference)
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_local_state() {
+        let contents = r#"{
+            "browser": {"enabled_labs_experiments": []},
+            "profile": {
+                "last_used": "Profile 1",
+                "info_cache": {
+                    "Default": {"name": "Personal", "avatar_icon": "x"},
+                    "Profile 1": {"name": "Work"}
+                }
+            }
+        }"#;
+        let profiles = parse_local_state(contents).unwrap();
+        assert_eq!(profiles.len(), 2);
+
+        let work = find_profile(&profiles, "work").unwrap();
+        assert_eq!(work.directory, "Profile 1");
+        assert!(work.is_default);
+        assert_eq!(find_profile(&profiles, "Default").unwrap().name, "Personal");
+        assert!(find_profile(&profiles, "Client").is_none());
+    }
+
+    #[test]
+    fn test_parse_profiles_ini() {
+        let contents = "\
+[Install4F96D1932A9F858E]
+Default=abcd.default-release
+
+[Profile1]
+Name=work
+IsRelative=1
+Path=efgh.work
+
+[Profile0]
+Name=default-release
+IsRelative=1
+Path=abcd.default-release
+Default=1
+
+[Profile2]
+Name=Scratch
+IsRelative=0
+Path=/tmp/scratch
+
+[General]
+StartWithLastProfile=1
+";
+        let profiles = parse_profiles_ini(contents, Path::new("/home/user/.mozilla/firefox"));
+        assert_eq!(profiles.len(), 3);
+        assert_eq!(profiles[0].directory, "/home/user/.mozilla/firefox/efgh.work");
+        assert!(profiles[1].is_default);
+        assert_eq!(find_profile(&profiles, "scratch").unwrap().directory, "/tmp/scratch");
+        assert_eq!(find_profile(&profiles, "efgh.work").unwrap().name, "work");
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+
+const PREFIX: &str = "brolaunch-ephemeral-";
+
+/// A throwaway browser profile directory, deleted when dropped
+#[derive(Debug)]
+pub struct EphemeralProfile {
+    path: PathBuf,
+}
+
+impl EphemeralProfile {
+    /// Creates the profile below `base_dir` (the system temp dir, or a directory a sandboxed browser can reach)
+    pub fn create(base_dir: &Path, profile: &str) -> io::Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        // The pid lets later runs recognise directories left behind by a killed brolaunch
+        let path = base_dir.join(format!("{}{}-{}-{}", PREFIX, std::process::id(), nanos, safe_name(profile)));
+        fs::create_dir_all(base_dir)?;
+        // Private to the user (cookies, history) and never an existing directory
+        fs::DirBuilder::new().mode(0o700).create(&path)?;
+        Ok(EphemeralProfile { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Path shown by `--dry-run` in place of a temporary profile, which is not created
+pub fn placeholder_path(base_dir: &Path, profile: &str) -> PathBuf {
+    base_dir.join(format!("{}DRY-RUN-{}", PREFIX, safe_name(profile)))
+}
+
+/// Profile name reduced to ASCII letters, digits and `_`, for use in directory names
+pub(crate) fn safe_name(profile: &str) -> String {
+    profile
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl Drop for EphemeralProfile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn owner_pid(dir_name: &str) -> Option<u32> {
+    dir_name.strip_prefix(PREFIX)?.split('-').next()?.parse().ok()
+}
+
+/// Deletes temporary profiles in `base_dir` whose brolaunch process no longer runs
+pub fn remove_stale(base_dir: &Path, verbose: bool) {
+    let Ok(entries) = fs::read_dir(base_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(pid) = owner_pid(&name) else {
+            continue;
+        };
+        if Path::new(&format!("/proc/{}", pid)).exists() {
+            continue;
+        }
+        if fs::remove_dir_all(entry.path()).is_ok() && verbose {
+            println!("🧹 Removed stale temporary profile {}", entry.path().display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_ephemeral_profile_is_removed_on_drop() {
+        let ephemeral = EphemeralProfile::create(&std::env::temp_dir(), "Work Stuff").unwrap();
+        let path = ephemeral.path().to_path_buf();
+        assert!(path.is_dir());
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o700);
+        assert_eq!(owner_pid(&path.file_name().unwrap().to_string_lossy()), Some(std::process::id()));
+
+        drop(ephemeral);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::Config;
+use crate::firefox::PrefValue;
+use crate::pattern::PatternEntry;
+use std::path::PathBuf;
+
+// XDG base directories have well-defined defaults, so configs can use them unset
+fn xdg_default(name: &str, home: Option<&PathBuf>) -> Option<String> {
+    let relative = match name {
+        "XDG_CONFIG_HOME" => ".config",
+        "XDG_DATA_HOME" => ".local/share",
+        "XDG_CACHE_HOME" => ".cache",
+        "XDG_STATE_HOME" => ".local/state",
+        _ => return None,
+    };
+    Some(home?.join(relative).display().to_string())
+}
+
+/// Expands a leading `~` and `$VAR`/`${VAR}` references (`$$` is a literal `$`).
+/// Referencing an unset variable is an error.
+pub fn expand_with(value: &str, lookup: impl Fn(&str) -> Option<String>, home: Option<PathBuf>) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+
+    if rest == "~" || rest.starts_with("~/") {
+        let home = home.as_ref().ok_or_else(|| format!("cannot expand '~' in '{}': home directory unknown", value))?;
+        expanded.push_str(&home.display().to_string());
+        rest = &rest[1..];
+    }
+
+    while let Some(index) = rest.find('$') {
+        expanded.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let (name, remainder) = if let Some(braced) = after.strip_prefix('{') {
+            let end = braced.find('}').ok_or_else(|| format!("unterminated '${{' in '{}'", value))?;
+            (&braced[..end], &braced[end + 1..])
+        } else if let Some(remainder) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = remainder;
+            continue;
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            if end == 0 {
+                // A lone `$` is kept as-is
+                expanded.push('$');
+                rest = after;
+                continue;
+            }
+            (&after[..end], &after[end..])
+        };
+
+        let resolved = lookup(name)
+            .or_else(|| xdg_default(name, home.as_ref()))
+            .ok_or_else(|| format!("environment variable '{}' is not set (used in '{}')", name, value))?;
+        expanded.push_str(&resolved);
+        rest = remainder;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+pub fn expand(value: &str) -> Result<String, String> {
+    expand_with(value, |name| std::env::var(name).ok(), dirs::home_dir())
+}
+
+fn expand_field(field: &mut Option<String>, path: &str) -> Result<(), String> {
+    if let Some(value) = field {
+        *value = expand(value).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+fn expand_list(list: &mut Option<Vec<String>>, path: &str) -> Result<(), String> {
+    for (index, value) in list.iter_mut().flatten().enumerate() {
+        *value = expand(value).map_err(|e| format!("{}[{}]: {}", path, index, e))?;
+    }
+    Ok(())
+}
+
+/// Expands `~` and environment variables in paths, binaries, browser commands, proxies, cli_flags, env, string prefs, wrappers, extension paths and window rules
+pub fn expand_config(config: &mut Config) -> Result<(), String> {
+    expand_field(&mut config.chromium_binary, "chromium_binary")?;
+    expand_field(&mut config.firefox_binary, "firefox_binary")?;
+    expand_list(&mut config.chromium_command, "chromium_command")?;
+    expand_list(&mut config.firefox_command, "firefox_command")?;
+
+    for (name, profile) in config.profiles.iter_mut() {
+        let path = format!("profiles.{}", name);
+        expand_field(&mut profile.user_data_dir, &format!("{}.user_data_dir", path))?;
+        expand_field(&mut profile.template_dir, &format!("{}.template_dir", path))?;
+        expand_field(&mut profile.firefox_app_profile_dir, &format!("{}.firefox_app_profile_dir", path))?;
+        expand_list(&mut profile.cli_flags, &format!("{}.cli_flags", path))?;
+        expand_list(&mut profile.hyprland_window_rules, &format!("{}.hyprland_window_rules", path))?;
+        expand_list(&mut profile.wrapper, &format!("{}.wrapper", path))?;
+        expand_list(&mut profile.extensions, &format!("{}.extensions", path))?;
+        expand_list(&mut profile.disable_extensions_except, &format!("{}.disable_extensions_except", path))?;
+        if let Some(proxy) = &mut profile.proxy {
+            expand_field(&mut proxy.server, &format!("{}.proxy.server", path))?;
+            expand_field(&mut proxy.pac_url, &format!("{}.proxy.pac_url", path))?;
+        }
+        for (key, value) in profile.env.iter_mut().flatten() {
+            *value = expand(value).map_err(|e| format!("{}.env.{}: {}", path, key, e))?;
+        }
+        for (key, value) in profile.prefs.iter_mut().flatten() {
+            if let PrefValue::String(value) = value {
+                *value = expand(value).map_err(|e| format!("{}.prefs.{}: {}", path, key, e))?;
+            }
+        }
+
+        for (list_name, entries) in [
+            ("patterns", &mut profile.patterns),
+            ("app_patterns", &mut profile.app_patterns),
+            ("private_patterns", &mut profile.private_patterns),
+        ] {
+            for (index, entry) in entries.iter_mut().flatten().enumerate() {
+                if let PatternEntry::Detailed(options) = entry {
+                    expand_list(&mut options.cli_flags, &format!("{}.{}[{}].cli_flags", path, list_name, index))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/alice".to_string()),
+            "PROFILE" => Some("work".to_string()),
+            _ => None,
+        }
+    }
+
+    fn home() -> Option<PathBuf> {
+        Some(PathBuf::from("/home/alice"))
+    }
+
+    #[test]
+    fn test_expand_tilde_and_variables() {
+        assert_eq!(expand_with("~/.config/x", lookup, home()).unwrap(), "/home/alice/.config/x");
+        assert_eq!(expand_with("$HOME/chromium-${PROFILE}", lookup, home()).unwrap(), "/home/alice/chromium-work");
+        assert_eq!(expand_with("${XDG_CONFIG_HOME}/brolaunch", lookup, home()).unwrap(), "/home/alice/.config/brolaunch");
+        assert_eq!(expand_with("a$$b $ c~", lookup, home()).unwrap(), "a$b $ c~");
+    }
+
+    #[test]
+    fn test_expand_unset_variable_is_an_error() {
+        let error = expand_with("--proxy=$PROXY_URL", lookup, home()).unwrap_err();
+        assert!(error.contains("'PROXY_URL' is not set"));
+        assert!(expand_with("${HOME", lookup, home()).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::Path;
+
+/// Checks that `dir` is an unpacked extension, i.e. has a readable, valid `manifest.json`
+pub fn validate_unpacked(dir: &str) -> Result<(), String> {
+    let manifest = Path::new(dir).join("manifest.json");
+    let contents = fs::read_to_string(&manifest).map_err(|e| format!("no manifest.json in {} ({})", dir, e))?;
+    serde_json::from_str::<serde_json::Value>(&contents)
+        .map_err(|e| format!("invalid {}: {}", manifest.display(), e))?;
+    Ok(())
+}
+
+/// `--load-extension` and `--disable-extensions-except` for the valid directories;
+/// invalid ones are reported and left out
+pub fn chromium_flags(extensions: &[String], disable_except: &[String]) -> Vec<String> {
+    let valid = |dirs: &[String]| -> Vec<String> {
+        dirs.iter()
+            .filter(|dir| match validate_unpacked(dir) {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("Skipping extension: {}", e);
+                    false
+                }
+            })
+            .cloned()
+            .collect()
+    };
+
+    let mut flags = Vec::new();
+    let load = valid(extensions);
+    if !load.is_empty() {
+        flags.push(format!("--load-extension={}", load.join(",")));
+    }
+    let except = valid(disable_except);
+    if !except.is_empty() {
+        flags.push(format!("--disable-extensions-except={}", except.join(",")));
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_dirs_need_a_manifest() {
+        let base = std::env::temp_dir().join(format!("brolaunch-extensions-test-{}", std::process::id()));
+        let good = base.join("good");
+        let broken = base.join("broken");
+        let empty = base.join("empty");
+        for dir in [&good, &broken, &empty] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(good.join("manifest.json"), r#"{"manifest_version": 3, "name": "Dev", "version": "1"}"#).unwrap();
+        fs::write(broken.join("manifest.json"), "{").unwrap();
+
+        let [good, broken, empty] = [good, broken, empty].map(|dir| dir.display().to_string());
+        assert!(validate_unpacked(&good).is_ok());
+        assert!(validate_unpacked(&broken).unwrap_err().starts_with("invalid"));
+        assert!(validate_unpacked(&empty).unwrap_err().starts_with("no manifest.json"));
+
+        assert_eq!(
+            chromium_flags(&[good.clone(), empty.clone()], std::slice::from_ref(&good)),
+            vec![format!("--load-extension={}", good), format!("--disable-extensions-except={}", good)]
+        );
+
+        fs::remove_dir_all(base).unwrap();
+    }
+}
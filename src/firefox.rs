@@ -0,0 +1,224 @@
+use crate::ephemeral::safe_name;
+use crate::sandbox::Sandbox;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use url::form_urlencoded;
+
+// Hides tabs, toolbars and the title bar so a window shows only the page
+const APP_USER_CHROME_CSS: &str = "\
+/* Generated by brolaunch for app windows; edit freely, it is not overwritten */
+#TabsToolbar, #nav-bar, #PersonalToolbar, #titlebar, #sidebar-header {
+  visibility: collapse !important;
+}
+#navigator-toolbox {
+  border: none !important;
+}
+";
+
+const APP_USER_JS: &str = "\
+// Generated by brolaunch for app windows; edit freely, it is not overwritten
+user_pref(\"toolkit.legacyUserProfileCustomizations.stylesheets\", true);
+user_pref(\"browser.shell.checkDefaultBrowser\", false);
+user_pref(\"browser.aboutwelcome.enabled\", false);
+user_pref(\"browser.tabs.warnOnClose\", false);
+user_pref(\"datareporting.policy.dataSubmissionPolicyBypassNotification\", true);
+";
+
+const MANAGED_BEGIN: &str = "// BEGIN brolaunch managed prefs (regenerated on every launch, edit the brolaunch config instead)";
+const MANAGED_END: &str = "// END brolaunch managed prefs";
+
+/// Value of a Firefox preference: a boolean, integer or string
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrefValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl PrefValue {
+    fn to_js(&self) -> String {
+        match self {
+            PrefValue::Bool(value) => value.to_string(),
+            PrefValue::Int(value) => value.to_string(),
+            PrefValue::String(value) => serde_json::Value::String(value.clone()).to_string(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PrefValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PrefVisitor;
+
+        impl Visitor<'_> for PrefVisitor {
+            type Value = PrefValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a boolean, integer or string (Firefox has no float prefs; quote the value)")
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<PrefValue, E> {
+                Ok(PrefValue::Bool(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<PrefValue, E> {
+                Ok(PrefValue::Int(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<PrefValue, E> {
+                i64::try_from(value).map(PrefValue::Int).map_err(|_| E::custom(format!("integer {} is too large", value)))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<PrefValue, E> {
+                Ok(PrefValue::String(value.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(PrefVisitor)
+    }
+}
+
+/// `user_js` with its brolaunch-managed block replaced by `prefs` (or removed when there are none)
+fn with_managed_prefs(user_js: &str, prefs: &[(String, PrefValue)]) -> String {
+    let mut lines = Vec::new();
+    let mut in_block = false;
+    for line in user_js.lines() {
+        if line == MANAGED_BEGIN {
+            in_block = true;
+        } else if in_block && line == MANAGED_END {
+            in_block = false;
+        } else if !in_block {
+            lines.push(line.to_string());
+        }
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    if !prefs.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(MANAGED_BEGIN.to_string());
+        for (name, value) in prefs {
+            lines.push(format!("user_pref({}, {});", serde_json::Value::String(name.clone()), value.to_js()));
+        }
+        lines.push(MANAGED_END.to_string());
+    }
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Writes `prefs` into the brolaunch-managed block of the profile's `user.js`,
+/// leaving the rest of the file alone. Returns whether the file changed.
+pub fn write_managed_prefs(profile_dir: &Path, prefs: &[(String, PrefValue)]) -> io::Result<bool> {
+    let path = profile_dir.join("user.js");
+    let existing = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let updated = with_managed_prefs(&existing, prefs);
+    if updated == existing || (updated.is_empty() && !path.exists()) {
+        return Ok(false);
+    }
+    fs::write(path, updated)?;
+    Ok(true)
+}
+
+/// URL understood by the "Open external links in a container" extension,
+/// which opens `url` in the Multi-Account Container named `container`
+pub fn container_url(container: &str, url: &str) -> String {
+    let name: String = form_urlencoded::byte_serialize(container.as_bytes()).collect();
+    let target: String = form_urlencoded::byte_serialize(url.as_bytes()).collect();
+    format!("ext+container:name={}&url={}", name, target)
+}
+
+/// Default location of the dedicated app-window profile for a configured profile;
+/// sandboxed browsers keep it in their own data directory
+pub fn default_app_profile_dir(sandbox: Option<&Sandbox>, profile: &str) -> PathBuf {
+    sandbox
+        .and_then(Sandbox::data_root)
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("brolaunch")
+        .join("firefox-apps")
+        .join(safe_name(profile))
+}
+
+/// Creates `profile_dir` as a minimal app-window profile. Files the user already
+/// has are left alone so customisations survive.
+pub fn prepare_app_profile(profile_dir: &Path) -> io::Result<()> {
+    let chrome_dir = profile_dir.join("chrome");
+    fs::create_dir_all(&chrome_dir)?;
+    for (path, contents) in [
+        (chrome_dir.join("userChrome.css"), APP_USER_CHROME_CSS),
+        (profile_dir.join("user.js"), APP_USER_JS),
+    ] {
+        if !path.exists() {
+            fs::write(path, contents)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_url_encodes_name_and_url() {
+        assert_eq!(
+            container_url("Work Stuff", "https://example.com/a?b=1&c=2"),
+            "ext+container:name=Work+Stuff&url=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1%26c%3D2"
+        );
+    }
+
+    #[test]
+    fn test_managed_prefs_block_is_replaced() {
+        let user_js = "user_pref(\"mine\", 1);\n";
+        let prefs = vec![
+            ("network.proxy.type".to_string(), PrefValue::Int(1)),
+            ("network.proxy.http".to_string(), PrefValue::String("proxy.corp".to_string())),
+        ];
+        let managed = with_managed_prefs(user_js, &prefs);
+        assert!(managed.starts_with("user_pref(\"mine\", 1);\n\n// BEGIN brolaunch"));
+        assert!(managed.contains("user_pref(\"network.proxy.http\", \"proxy.corp\");\n"));
+
+        let replaced = with_managed_prefs(&managed, &[("a".to_string(), PrefValue::Bool(false))]);
+        assert!(!replaced.contains("network.proxy"));
+        assert!(replaced.contains("user_pref(\"a\", false);"));
+        assert_eq!(with_managed_prefs(&replaced, &[]), user_js);
+    }
+
+    #[test]
+    fn test_pref_values_deserialize() {
+        let prefs: std::collections::BTreeMap<String, PrefValue> =
+            serde_yaml::from_str("a: true\nb: 3\nc: \"x\\\"y\"\n").unwrap();
+        assert_eq!(prefs["a"], PrefValue::Bool(true));
+        assert_eq!(prefs["b"], PrefValue::Int(3));
+        assert_eq!(prefs["c"].to_js(), "\"x\\\"y\"");
+        let error = serde_yaml::from_str::<std::collections::BTreeMap<String, PrefValue>>("a: 1.5\n").unwrap_err();
+        assert!(error.to_string().contains("no float prefs"));
+    }
+
+    #[test]
+    fn test_prepare_app_profile_keeps_user_edits() {
+        let dir = std::env::temp_dir().join(format!("brolaunch-firefox-app-test-{}", std::process::id()));
+        prepare_app_profile(&dir).unwrap();
+        let user_js = fs::read_to_string(dir.join("user.js")).unwrap();
+        assert!(user_js.contains("toolkit.legacyUserProfileCustomizations.stylesheets\", true"));
+
+        fs::write(dir.join("chrome").join("userChrome.css"), "/* mine */").unwrap();
+        prepare_app_profile(&dir).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("chrome").join("userChrome.css")).unwrap(), "/* mine */");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
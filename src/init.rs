@@ -0,0 +1,203 @@
+use crate::BrowserType;
+use crate::desktop_dialog::DesktopEnvironment;
+use crate::discovery::{self, BrowserProfile};
+use crate::sandbox::{self, Sandbox};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// An installed browser and the profiles it already has
+#[derive(Debug)]
+pub struct DetectedBrowser {
+    pub name: &'static str,
+    pub browser_type: BrowserType,
+    /// Launch command: a binary, or e.g. `flatpak run <app-id>` for sandboxed browsers
+    pub command: Vec<String>,
+    pub profiles: Vec<BrowserProfile>,
+}
+
+// Candidate binaries per browser, best first. The Arch chromium wrapper
+// script duplicates flags, so the real binary is preferred when present.
+const CHROMIUM_FAMILY: &[(&str, &[&str])] = &[
+    ("Chromium", &["/usr/lib/chromium/chromium", "chromium", "chromium-browser"]),
+    ("Google Chrome", &["google-chrome-stable", "google-chrome"]),
+    ("Brave", &["brave", "brave-browser"]),
+    ("Vivaldi", &["vivaldi-stable", "vivaldi"]),
+    ("Microsoft Edge", &["microsoft-edge-stable", "microsoft-edge"]),
+];
+
+fn find_binary(candidates: &[&str]) -> Option<String> {
+    candidates
+        .iter()
+        .find(|candidate| {
+            if candidate.starts_with('/') {
+                Path::new(candidate).exists()
+            } else {
+                DesktopEnvironment::command_exists(candidate)
+            }
+        })
+        .map(|candidate| candidate.to_string())
+}
+
+pub fn detect_browsers() -> Vec<DetectedBrowser> {
+    let mut browsers = Vec::new();
+
+    for (name, candidates) in CHROMIUM_FAMILY {
+        let Some(binary) = find_binary(candidates) else {
+            continue;
+        };
+        browsers.push(detected(name, BrowserType::Chromium, vec![binary]));
+    }
+
+    if let Some(binary) = find_binary(&["firefox"]) {
+        browsers.push(detected("Firefox", BrowserType::Firefox, vec![binary]));
+    }
+
+    // A snap on $PATH was already found above under its plain name
+    let found: Vec<Sandbox> = browsers.iter().filter_map(|browser| Sandbox::from_command(&browser.command)).collect();
+    for (name, browser_type, command) in sandbox::detect_installed() {
+        if Sandbox::from_command(&command).is_some_and(|sandbox| !found.contains(&sandbox)) {
+            browsers.push(detected(name, browser_type, command));
+        }
+    }
+
+    browsers
+}
+
+fn detected(name: &'static str, browser_type: BrowserType, command: Vec<String>) -> DetectedBrowser {
+    let profiles = match browser_type {
+        BrowserType::Chromium => discovery::chromium_user_data_dir(&command)
+            .and_then(|dir| discovery::read_chromium_profiles(&dir).ok()),
+        BrowserType::Firefox => discovery::firefox_profiles_dir(&command)
+            .and_then(|dir| discovery::read_firefox_profiles(&dir).ok()),
+    };
+    DetectedBrowser {
+        name,
+        browser_type,
+        command,
+        profiles: profiles.unwrap_or_default(),
+    }
+}
+
+/// Picks the requested browser type, or the first browser that already has profiles
+pub fn select_browser(browsers: Vec<DetectedBrowser>, requested: Option<&BrowserType>) -> Option<DetectedBrowser> {
+    let mut candidates: Vec<DetectedBrowser> = browsers
+        .into_iter()
+        .filter(|browser| requested.is_none_or(|requested| browser.browser_type == *requested))
+        .collect();
+    let index = candidates.iter().position(|browser| !browser.profiles.is_empty()).unwrap_or(0);
+    if candidates.is_empty() {
+        None
+    } else {
+        Some(candidates.swap_remove(index))
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn generate_config(browser: &DetectedBrowser) -> String {
+    let mut yaml = String::new();
+    yaml.push_str(&format!("# Generated by `brolaunch init` for {}\n", browser.name));
+    yaml.push_str("# Add regex patterns to route URLs to each profile.\n\n");
+
+    let browser_key = match browser.browser_type {
+        BrowserType::Chromium => "chromium",
+        BrowserType::Firefox => "firefox",
+    };
+    yaml.push_str(&format!("browser_type: {}\n", browser_key));
+    match browser.command.as_slice() {
+        [binary] => yaml.push_str(&format!("{}_binary: {}\n", browser_key, quote(binary))),
+        command => {
+            let words: Vec<String> = command.iter().map(|word| quote(word)).collect();
+            yaml.push_str(&format!("{}_command: [{}]\n", browser_key, words.join(", ")));
+        }
+    }
+
+    // Config keys must be unique even if two browser profiles share a display name
+    let mut used_names = HashSet::new();
+    let keys: Vec<String> = browser
+        .profiles
+        .iter()
+        .map(|profile| {
+            let mut key = profile.name.clone();
+            if !used_names.insert(key.clone()) {
+                key = format!("{} ({})", profile.name, profile.directory);
+                used_names.insert(key.clone());
+            }
+            key
+        })
+        .collect();
+
+    if let Some(default_index) = browser.profiles.iter().position(|profile| profile.is_default) {
+        yaml.push_str(&format!("\ndefault_profile: {}\n", quote(&keys[default_index])));
+    }
+
+    if browser.profiles.is_empty() {
+        yaml.push_str("\n# No existing profiles were found; add your own below.\nprofiles: {}\n");
+        return yaml;
+    }
+
+    yaml.push_str("\nprofiles:\n");
+    for (key, profile) in keys.iter().zip(&browser.profiles) {
+        yaml.push_str(&format!("  {}:\n", quote(key)));
+        let reference = match browser.browser_type {
+            BrowserType::Chromium => &profile.directory,
+            BrowserType::Firefox => &profile.name,
+        };
+        yaml.push_str(&format!("    browser_profile: {}\n", quote(reference)));
+        yaml.push_str("    patterns: []\n");
+        yaml.push_str("    app_patterns: []\n");
+    }
+    yaml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, directory: &str, is_default: bool) -> BrowserProfile {
+        BrowserProfile {
+            name: name.to_string(),
+            directory: directory.to_string(),
+            is_default,
+        }
+    }
+
+    #[test]
+    fn test_generate_chromium_config() {
+        let browser = DetectedBrowser {
+            name: "Chromium",
+            browser_type: BrowserType::Chromium,
+            command: vec!["/usr/lib/chromium/chromium".to_string()],
+            profiles: vec![
+                profile("Personal", "Default", false),
+                profile("Work", "Profile 1", true),
+                profile("Work", "Profile 2", false),
+            ],
+        };
+        let yaml = generate_config(&browser);
+        let config: crate::Config = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(config.chromium_binary.as_deref(), Some("/usr/lib/chromium/chromium"));
+        assert_eq!(config.default_profile.as_deref(), Some("Work"));
+        assert_eq!(config.profiles.len(), 3);
+        let duplicate = &config.profiles["Work (Profile 2)"];
+        assert_eq!(duplicate.browser_profile.as_deref(), Some("Profile 2"));
+        assert!(duplicate.patterns.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_generate_config_without_profiles() {
+        let browser = DetectedBrowser {
+            name: "Firefox",
+            browser_type: BrowserType::Firefox,
+            command: vec!["flatpak".to_string(), "run".to_string(), "org.mozilla.firefox".to_string()],
+            profiles: vec![],
+        };
+        let config: crate::Config = serde_yaml::from_str(&generate_config(&browser)).unwrap();
+        assert_eq!(config.browser_type, Some(BrowserType::Firefox));
+        assert_eq!(config.firefox_command.unwrap(), ["flatpak", "run", "org.mozilla.firefox"]);
+        assert!(config.profiles.is_empty());
+    }
+}
@@ -1,14 +1,37 @@
 use clap::{Arg, Command as ClapCommand};
+use jiff::Zoned;
 use regex::Regex;
 use serde::Deserialize;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
+mod bootstrap;
+mod config_files;
 mod desktop_dialog;
-use desktop_dialog::{DesktopEnvironment, SessionType};
+mod discovery;
+mod ephemeral;
+mod expand;
+mod extensions;
+mod firefox;
+mod init;
+mod pattern;
+mod proxy;
+mod routes;
+mod sandbox;
+mod schedule;
+mod tui_chooser;
+mod url_unwrap;
+use desktop_dialog::{ChooserConfig, ChooserSelection, DesktopEnvironment, OpenMode, SessionType};
+use ephemeral::EphemeralProfile;
+use firefox::PrefValue;
+use pattern::{PatternEntry, WindowOptions};
+use proxy::ProxyConfig;
+use routes::LearnedRoutes;
+use sandbox::Sandbox;
+use schedule::Schedule;
+use url_unwrap::UnwrapRule;
 
 fn is_hyprland() -> bool {
     if let Ok(desktop) = std::env::var("XDG_CURRENT_DESKTOP") {
@@ -18,7 +41,53 @@ fn is_hyprland() -> bool {
     }
 }
 
-fn apply_hyprland_rules(profile_config: &ProfileConfig, browser_type: &BrowserType, verbose: bool) {
+fn is_sway() -> bool {
+    std::env::var("SWAYSOCK").is_ok()
+        || std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.to_lowercase().contains("sway"))
+}
+
+/// Regex for the class/app_id of the window a launch opens, if it has one of its own.
+/// Only Chromium app windows do (`chrome-<host>__<path>-<profile>`); other windows share
+/// the browser's class, so a rule on it would catch every window of that browser.
+fn window_class_pattern(browser_type: &BrowserType, url: Option<&str>, window_mode: WindowMode) -> Option<String> {
+    match browser_type {
+        BrowserType::Chromium => {
+            let host = url.filter(|_| window_mode == WindowMode::App).and_then(|url| url::Url::parse(url).ok()?.host_str().map(str::to_string))?;
+            Some(format!("^(chrome-{}__.*)$", regex::escape(&host)))
+        }
+        BrowserType::Firefox => None,
+    }
+}
+
+/// Floats the launched window and applies the pattern's size/position on Hyprland and Sway.
+/// Must run before the browser starts so the rules catch the new window.
+fn apply_window_geometry_rules(window: &WindowOptions, class_pattern: &str, verbose: bool) {
+    if is_hyprland() {
+        let mut rules = vec![format!("windowrulev2 = float,class:{}", class_pattern)];
+        if let Some(pattern::WindowPair(width, height)) = window.window_size {
+            rules.push(format!("windowrulev2 = size {} {},class:{}", width, height, class_pattern));
+        }
+        if let Some(pattern::WindowPair(x, y)) = window.window_position {
+            rules.push(format!("windowrulev2 = move {} {},class:{}", x, y, class_pattern));
+        }
+        run_hyprctl_keywords(&rules, verbose);
+    } else if is_sway() {
+        let mut commands = vec!["floating enable".to_string()];
+        if let Some(pattern::WindowPair(width, height)) = window.window_size {
+            commands.push(format!("resize set {} {}", width, height));
+        }
+        if let Some(pattern::WindowPair(x, y)) = window.window_position {
+            commands.push(format!("move position {} {}", x, y));
+        }
+        let rule = format!("for_window [app_id=\"{}\"] {}", class_pattern, commands.join(", "));
+        if verbose {
+            println!("🔧 Applying Sway rule: {}", rule);
+        }
+        let _ = Command::new("swaymsg").arg(&rule).status();
+    }
+}
+
+fn apply_hyprland_rules(profile_config: &ProfileConfig, workspace: Option<&str>, browser_type: &BrowserType, verbose: bool) {
     if !is_hyprland() {
         return;
     }
@@ -26,7 +95,7 @@ fn apply_hyprland_rules(profile_config: &ProfileConfig, browser_type: &BrowserTy
     let mut rules = Vec::new();
 
     // Add workspace rule
-    if let Some(workspace) = &profile_config.hyprland_workspace {
+    if let Some(workspace) = workspace.or(profile_config.hyprland_workspace.as_deref()) {
         match browser_type {
             BrowserType::Chromium => {
                 rules.push(format!("windowrulev2 = workspace {},class:(chromium)", workspace));
@@ -56,15 +125,16 @@ fn apply_hyprland_rules(profile_config: &ProfileConfig, browser_type: &BrowserTy
         rules.extend(custom_rules.clone());
     }
 
-    // Apply rules using hyprctl
+    run_hyprctl_keywords(&rules, verbose);
+}
+
+/// Applies Hyprland window rules for the session with `hyprctl keyword`
+fn run_hyprctl_keywords(rules: &[String], verbose: bool) {
     for rule in rules {
         if verbose {
             println!("🔧 Applying Hyprland rule: {}", rule);
         }
-        let _ = std::process::Command::new("hyprctl")
-            .arg("keyword")
-            .arg(&rule)
-            .status();
+        let _ = Command::new("hyprctl").arg("keyword").arg(rule).status();
     }
 }
 
@@ -77,21 +147,123 @@ enum BrowserType {
 
 #[derive(Debug, Deserialize)]
 struct ProfileConfig {
+    // Profile or template this one was built on (already applied at load time)
+    extends: Option<String>,
     user_data_dir: Option<String>,
+    // Display name (or directory) of an existing browser profile
+    browser_profile: Option<String>,
+    // Create the profile before launching if it doesn't exist yet
+    create_if_missing: Option<bool>,
+    template_dir: Option<String>,
+    // Firefox Multi-Account Container to open URLs in
+    container: Option<String>,
+    // Dedicated profile used for Firefox app windows
+    firefox_app_profile_dir: Option<String>,
     app_mode: Option<bool>,
-    patterns: Option<Vec<String>>,
-    app_patterns: Option<Vec<String>>,
+    patterns: Option<Vec<PatternEntry>>,
+    app_patterns: Option<Vec<PatternEntry>>,
+    // URLs that open in an incognito/private window of the profile
+    private_patterns: Option<Vec<PatternEntry>>,
     cli_flags: Option<Vec<String>>,
+    // Proxy server, bypass list or PAC URL for the profile's traffic
+    proxy: Option<ProxyConfig>,
+    // Firefox preferences written to the managed block of the profile's user.js
+    prefs: Option<BTreeMap<String, PrefValue>>,
+    // Only route URLs to this profile while the condition holds
+    when: Option<Schedule>,
+    // Unpacked extension directories to load, and the ones to keep when disabling all others (Chromium)
+    extensions: Option<Vec<String>>,
+    disable_extensions_except: Option<Vec<String>>,
+    // Extra environment variables for the browser process
+    env: Option<BTreeMap<String, String>>,
+    // Command the browser is run under, e.g. ["firejail", "--net=none"]
+    wrapper: Option<Vec<String>>,
     // Hyprland-specific options
     hyprland_workspace: Option<String>,
     hyprland_monitor: Option<String>,
     hyprland_window_rules: Option<Vec<String>>,
 }
 
+/// How the URL is presented once the browser starts
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum WindowMode {
+    #[serde(rename = "window")]
+    NewWindow,
+    #[serde(rename = "existing")]
+    ExistingWindow,
+    App,
+    Private,
+}
+
+impl WindowMode {
+    fn from_app_mode(app_mode: bool) -> Self {
+        if app_mode { WindowMode::App } else { WindowMode::NewWindow }
+    }
+
+    /// Applies the --private, --app and --existing CLI flags on top of a configured mode
+    fn with_cli_flags(self, app: bool, private: bool, existing: bool) -> Self {
+        if private {
+            WindowMode::Private
+        } else if app {
+            WindowMode::App
+        } else if existing && self == WindowMode::NewWindow {
+            WindowMode::ExistingWindow
+        } else {
+            self
+        }
+    }
+}
+
+/// Everything `launch_browser` needs for one launch, resolved from the matched
+/// pattern entry (or the chooser/CLI) before anything is started
 #[derive(Debug)]
-struct ProfileMatch {
+struct LaunchPlan {
     profile: String,
-    app_mode: bool,
+    url: Option<String>,
+    window_mode: WindowMode,
+    window: WindowOptions,
+    ephemeral: bool,
+    // Extra flags from the pattern entry, after the profile's cli_flags
+    cli_flags: Vec<String>,
+    // Overrides the profile's hyprland_workspace
+    workspace: Option<String>,
+    // Overrides the profile's container
+    container: Option<String>,
+    // Matched a route remembered from the chooser rather than a config pattern
+    learned: bool,
+}
+
+impl LaunchPlan {
+    fn new(profile: &str, url: Option<&str>, window_mode: WindowMode, ephemeral: bool) -> Self {
+        LaunchPlan {
+            profile: profile.to_string(),
+            url: url.map(str::to_string),
+            window_mode,
+            window: WindowOptions::default(),
+            ephemeral,
+            cli_flags: Vec::new(),
+            workspace: None,
+            container: None,
+            learned: false,
+        }
+    }
+
+    fn from_pattern(profile: &str, url: &str, entry: &PatternEntry, captures: &regex::Captures, window_mode: WindowMode) -> Self {
+        let mut plan = LaunchPlan::new(profile, Some(url), window_mode, false);
+        plan.window = entry.window();
+        if let Some(options) = entry.options() {
+            if let Some(rewritten) = options.rewrite_url(captures) {
+                plan.url = Some(rewritten);
+            }
+            plan.window_mode = options.window_mode().unwrap_or(window_mode);
+            plan.ephemeral = options.ephemeral.unwrap_or(false);
+            plan.cli_flags = options.cli_flags.clone().unwrap_or_default();
+            plan.workspace = options.workspace.clone();
+            plan.container = options.container.clone();
+        }
+        plan
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -99,8 +271,21 @@ struct Config {
     browser_type: Option<BrowserType>,
     chromium_binary: Option<String>,
     firefox_binary: Option<String>,
+    // Multi-word launch commands (e.g. `flatpak run com.google.Chrome`), used instead of *_binary
+    chromium_command: Option<Vec<String>>,
+    firefox_command: Option<Vec<String>>,
+    // Open Firefox app windows with --kiosk instead of a toolbar-less window
+    firefox_app_kiosk: Option<bool>,
     default_profile: Option<String>,
     profiles: HashMap<String, ProfileConfig>,
+    // Redirect unwrapping (SafeLinks, Google /url, Slack, Teams)
+    unwrap_redirects: Option<bool>,
+    unwrap_rules: Option<Vec<UnwrapRule>>,
+    // Profile chooser backend selection
+    chooser: Option<ChooserConfig>,
+    // Seconds before an unanswered chooser closes, and the profile used then
+    chooser_timeout: Option<u64>,
+    fallback_profile: Option<String>,
 }
 
 fn find_config_file(config_path: Option<&str>) -> Result<PathBuf, Box<dyn std::error::Error>> {
@@ -123,45 +308,107 @@ fn find_config_file(config_path: Option<&str>) -> Result<PathBuf, Box<dyn std::e
     Err("No config file found. Looked for ~/.config/brolaunch.yaml and ./config.yaml".into())
 }
 
-fn load_config(path: &PathBuf) -> Result<Config, Box<dyn std::error::Error>> {
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    let config: Config = serde_yaml::from_str(&contents)?;
-    Ok(config)
+/// Loads the config with its includes, drop-ins, `extends` and `~`/`$VAR` expansion;
+/// also reports what was merged
+fn load_config(path: &std::path::Path) -> Result<(Config, config_files::LoadReport), Box<dyn std::error::Error>> {
+    let layered = config_files::load(path)?;
+    let mut config: Config = serde_yaml::from_value(layered.value)?;
+    expand::expand_config(&mut config)?;
+    for (name, profile) in &config.profiles {
+        if let Some(proxy) = &profile.proxy {
+            proxy.validate().map_err(|e| format!("profiles.{}.proxy: {}", name, e))?;
+        }
+    }
+    Ok((config, layered.report))
 }
 
-fn match_profile(url: &str, config: &Config) -> Option<ProfileMatch> {
+/// Entries of a pattern list that apply at `now` and match `url`, in order
+fn matching_patterns<'a, 'u>(
+    entries: &'a Option<Vec<PatternEntry>>,
+    url: &'u str,
+    now: &Zoned,
+) -> impl Iterator<Item = (&'a PatternEntry, regex::Captures<'u>)> {
+    entries
+        .iter()
+        .flatten()
+        .filter(|entry| entry.when().is_none_or(|when| when.matches(now)))
+        .filter_map(move |entry| Some((entry, Regex::new(entry.pattern()).ok()?.captures(url)?)))
+}
+
+/// Finds the profile for `url` at time `now`. A match gated by a `when:` condition beats
+/// one without, so the same pattern can go to different profiles at different times.
+fn match_profile(url: &str, config: &Config, learned_routes: &LearnedRoutes, now: &Zoned) -> Option<LaunchPlan> {
+    let mut unconditional = None;
     for (profile_name, profile_config) in &config.profiles {
-        // Check app_patterns first (higher priority)
-        if let Some(app_patterns) = &profile_config.app_patterns {
-            for pattern in app_patterns {
-                if let Ok(re) = Regex::new(pattern) {
-                    if re.is_match(url) {
-                        return Some(ProfileMatch {
-                            profile: profile_name.clone(),
-                            app_mode: true,
-                        });
-                    }
+        if profile_config.when.as_ref().is_some_and(|when| !when.matches(now)) {
+            continue;
+        }
+        let default_mode = WindowMode::from_app_mode(profile_config.app_mode.unwrap_or(false));
+        // app_patterns first (higher priority), then private and regular patterns
+        let candidates = [
+            (&profile_config.app_patterns, WindowMode::App),
+            (&profile_config.private_patterns, WindowMode::Private),
+            (&profile_config.patterns, default_mode),
+        ];
+        // Past an ungated match, keep looking: a gated entry further down still wins
+        for (entries, window_mode) in candidates {
+            for (entry, captures) in matching_patterns(entries, url, now) {
+                if profile_config.when.is_some() || entry.when().is_some() {
+                    return Some(LaunchPlan::from_pattern(profile_name, url, entry, &captures, window_mode));
+                }
+                if unconditional.is_none() {
+                    unconditional = Some(LaunchPlan::from_pattern(profile_name, url, entry, &captures, window_mode));
                 }
             }
         }
-        
-        // Check regular patterns (window mode)
-        if let Some(patterns) = &profile_config.patterns {
-            for pattern in patterns {
-                if let Ok(re) = Regex::new(pattern) {
-                    if re.is_match(url) {
-                        return Some(ProfileMatch {
-                            profile: profile_name.clone(),
-                            app_mode: profile_config.app_mode.unwrap_or(false),
-                        });
-                    }
+    }
+    if unconditional.is_some() {
+        return unconditional;
+    }
+
+    // Fall back to decisions remembered from the profile chooser, unless the profile's `when` rules it out now
+    let profile = learned_routes.lookup(url, |profile| {
+        config
+            .profiles
+            .get(profile)
+            .is_some_and(|profile_config| profile_config.when.as_ref().is_none_or(|when| when.matches(now)))
+    })?;
+    let profile_config = config.profiles.get(profile)?;
+    let mut plan = LaunchPlan::new(profile, Some(url), WindowMode::from_app_mode(profile_config.app_mode.unwrap_or(false)), false);
+    plan.learned = true;
+    Some(plan)
+}
+
+fn run_routes_command(matches: &clap::ArgMatches) {
+    let path = LearnedRoutes::default_path();
+    let mut learned_routes = match LearnedRoutes::load(&path) {
+        Ok(routes) => routes,
+        Err(e) => {
+            eprintln!("Failed to load learned routes from {:?}: {}", path, e);
+            return;
+        }
+    };
+
+    match matches.subcommand() {
+        Some(("list", _)) => {
+            println!("📋 Learned routes ({}):", learned_routes.path().display());
+            for (domain, profile) in learned_routes.iter() {
+                println!("  {} → {}", domain, profile);
+            }
+        }
+        Some(("forget", sub_matches)) => {
+            let domain = sub_matches.get_one::<String>("domain").expect("domain is required");
+            if learned_routes.forget(domain) {
+                match learned_routes.save() {
+                    Ok(()) => println!("Forgot learned route for {}", domain),
+                    Err(e) => eprintln!("Failed to save learned routes: {}", e),
                 }
+            } else {
+                eprintln!("No learned route for {}", domain);
             }
         }
+        _ => unreachable!("routes subcommand is required"),
     }
-    None
 }
 
 fn should_include_flag(flag: &str, session_type: &SessionType) -> bool {
@@ -182,12 +429,267 @@ fn should_include_flag(flag: &str, session_type: &SessionType) -> bool {
     }
 }
 
-fn launch_browser(binary: &str, browser_type: &BrowserType, profile: &str, url: Option<&str>, config: &Config, verbose: bool, app_mode: bool, existing: bool) {
-    let mut cmd = Command::new(binary);
+/// Appends configured `cli_flags`, leaving out Wayland-only flags for Chromium on X11
+fn push_profile_flags(cmd: &mut Command, args: &mut Vec<String>, browser_type: &BrowserType, flags: &[String], verbose: bool) {
+    if flags.is_empty() {
+        return;
+    }
+    let session_type = DesktopEnvironment::detect_session_type();
+    for flag in flags {
+        if *browser_type == BrowserType::Chromium && !should_include_flag(flag, &session_type) {
+            if verbose {
+                println!("🚫 Skipping flag '{}' (not compatible with {:?})", flag, session_type);
+            }
+            continue;
+        }
+        cmd.arg(flag);
+        args.push(flag.clone());
+    }
+}
+
+/// Maps a profile to the directory Chromium expects in `--profile-directory`.
+/// Chromium names its directories `Default`, `Profile 1`, ..., so display names
+/// are looked up in the `Local State` file of the user data directory.
+fn resolve_chromium_profile_directory(command: &[String], profile: &str, profile_config: Option<&ProfileConfig>, verbose: bool) -> String {
+    let browser_profile = profile_config.and_then(|p| p.browser_profile.as_deref());
+    let reference = browser_profile.unwrap_or(profile);
+
+    let user_data_dir = profile_config
+        .and_then(|p| p.user_data_dir.as_ref())
+        .map(PathBuf::from)
+        .or_else(|| discovery::chromium_user_data_dir(command));
+    let discovered = match user_data_dir.as_deref().map(discovery::read_chromium_profiles) {
+        Some(Ok(profiles)) => profiles,
+        Some(Err(e)) => {
+            if verbose {
+                println!("⚠️  Could not read Chromium Local State: {}", e);
+            }
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
+    match discovery::find_profile(&discovered, reference) {
+        Some(found) => {
+            if verbose && found.directory != reference {
+                println!("🔎 Browser profile '{}' → directory '{}'", reference, found.directory);
+            }
+            found.directory.clone()
+        }
+        None => {
+            if verbose && browser_profile.is_some() {
+                println!("⚠️  Browser profile '{}' not found in Local State, using it as a directory name", reference);
+            }
+            reference.to_string()
+        }
+    }
+}
+
+/// Maps a profile to the exact name registered in Firefox's `profiles.ini`
+fn resolve_firefox_profile_name(command: &[String], profile: &str, profile_config: Option<&ProfileConfig>, verbose: bool) -> String {
+    let browser_profile = profile_config.and_then(|p| p.browser_profile.as_deref());
+    let reference = browser_profile.unwrap_or(profile);
+
+    let discovered = discovery::firefox_profiles_dir(command)
+        .and_then(|dir| discovery::read_firefox_profiles(&dir).ok())
+        .unwrap_or_default();
+
+    match discovery::find_profile(&discovered, reference) {
+        Some(found) => {
+            if verbose && found.name != reference {
+                println!("🔎 Firefox profile '{}' → '{}'", reference, found.name);
+            }
+            found.name.clone()
+        }
+        None => {
+            if verbose {
+                println!("⚠️  Firefox profile '{}' not found in profiles.ini", reference);
+            }
+            reference.to_string()
+        }
+    }
+}
+
+/// The command that starts the browser: `*_command` when set, otherwise `*_binary`
+fn browser_command(config: &Config, browser_type: &BrowserType) -> Vec<String> {
+    let (command, binary, default) = match browser_type {
+        BrowserType::Chromium => (&config.chromium_command, &config.chromium_binary, "chromium"),
+        BrowserType::Firefox => (&config.firefox_command, &config.firefox_binary, "firefox"),
+    };
+    match command {
+        Some(command) if !command.is_empty() => command.clone(),
+        _ => vec![binary.as_deref().unwrap_or(default).to_string()],
+    }
+}
+
+/// Preferences brolaunch maintains in a Firefox profile's user.js
+fn firefox_managed_prefs(profile_config: &ProfileConfig) -> Result<Vec<(String, PrefValue)>, String> {
+    let mut prefs = match &profile_config.proxy {
+        Some(proxy) => proxy.firefox_prefs()?,
+        None => Vec::new(),
+    };
+    // Explicit prefs win over ones derived from other options
+    if let Some(explicit) = &profile_config.prefs {
+        prefs.retain(|(name, _)| !explicit.contains_key(name));
+        prefs.extend(explicit.iter().map(|(name, value)| (name.clone(), value.clone())));
+    }
+    Ok(prefs)
+}
+
+/// Quotes an argument for display so dry-run output can be pasted into a shell
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_=/.,:@%+".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// `ENV=value wrapper... browser args...` as printed by `--dry-run`
+fn dry_run_command_line(env: Option<&BTreeMap<String, String>>, command: &[&String], args: &[String]) -> String {
+    let words: Vec<String> = env
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| format!("{}={}", name, shell_quote(value)))
+        .chain(command.iter().map(|arg| shell_quote(arg)))
+        .chain(args.iter().map(|arg| shell_quote(arg)))
+        .collect();
+    words.join(" ")
+}
+
+/// Window-mode flag and URL that end the command line; `url` is already wrapped for a Firefox container.
+/// Chromium's kiosk flag comes from the window options instead.
+fn window_mode_args(browser_type: &BrowserType, window_mode: WindowMode, kiosk: bool, url: Option<String>) -> Vec<String> {
+    if *browser_type == BrowserType::Chromium && window_mode == WindowMode::App && let Some(url) = &url {
+        return vec![format!("--app={}", url)];
+    }
+    let mode_flag = match (browser_type, window_mode) {
+        (BrowserType::Chromium, WindowMode::Private) => Some("--incognito"),
+        (BrowserType::Chromium, WindowMode::ExistingWindow) => None,
+        (BrowserType::Chromium, _) => Some("--new-window"),
+        (BrowserType::Firefox, WindowMode::Private) => Some("-private-window"),
+        (BrowserType::Firefox, _) if kiosk => Some("--kiosk"),
+        (BrowserType::Firefox, WindowMode::ExistingWindow) => None,
+        (BrowserType::Firefox, _) => Some("-new-window"),
+    };
+    mode_flag.map(str::to_string).into_iter().chain(url).collect()
+}
+
+fn launch_browser(command: &[String], browser_type: &BrowserType, plan: &LaunchPlan, config: &Config, verbose: bool, dry_run: bool) {
+    let wrapper = config
+        .profiles
+        .get(&plan.profile)
+        .and_then(|p| p.wrapper.as_deref())
+        .unwrap_or_default();
+    let full_command: Vec<&String> = wrapper.iter().chain(command).collect();
+    let Some((program, command_args)) = full_command.split_first() else {
+        eprintln!("Browser command is empty");
+        return;
+    };
+    let mut cmd = Command::new(program);
+    cmd.args(command_args);
+    let sandbox = Sandbox::from_command(command);
+    let env = config.profiles.get(&plan.profile).and_then(|p| p.env.as_ref());
+    if let Some(env) = env {
+        cmd.envs(env);
+    }
     let mut args = Vec::new();
+    let profile = plan.profile.as_str();
+    let url = plan.url.as_deref();
+    let window_mode = plan.window_mode;
+    let window = &plan.window;
+    let ephemeral = plan.ephemeral;
+    let container = plan
+        .container
+        .as_deref()
+        .or_else(|| config.profiles.get(profile).and_then(|p| p.container.as_deref()));
+
+    // Sandboxed browsers have a private /tmp, so their temporary profiles live in the sandbox
+    let ephemeral_base_dir = sandbox.as_ref().and_then(Sandbox::temp_dir).unwrap_or_else(std::env::temp_dir);
+    let ephemeral_profile = if ephemeral && !dry_run {
+        ephemeral::remove_stale(&ephemeral_base_dir, verbose);
+        match EphemeralProfile::create(&ephemeral_base_dir, profile) {
+            Ok(ephemeral_profile) => Some(ephemeral_profile),
+            Err(e) => {
+                eprintln!("Failed to create temporary profile: {}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    // --dry-run prints a placeholder instead of creating (and deleting) a real directory
+    let ephemeral_dir = match &ephemeral_profile {
+        Some(ephemeral_profile) => Some(ephemeral_profile.path().to_path_buf()),
+        None if ephemeral => Some(ephemeral::placeholder_path(&ephemeral_base_dir, profile)),
+        None => None,
+    };
     
+    // Firefox has no --app, so app windows use a profile whose userChrome.css hides the UI
+    let firefox_app = *browser_type == BrowserType::Firefox && window_mode == WindowMode::App && url.is_some();
+    let kiosk = match browser_type {
+        BrowserType::Chromium => window.kiosk.unwrap_or(false),
+        BrowserType::Firefox => window.kiosk.unwrap_or(firefox_app && config.firefox_app_kiosk.unwrap_or(false)),
+    };
+
     let mut user_data_dir_used = None;
-    if let Some(profile_config) = config.profiles.get(profile) {
+    // Firefox profile directory whose user.js gets the managed prefs block
+    let mut firefox_profile_dir = None;
+    if let Some(ephemeral_dir) = &ephemeral_dir {
+        let temp_dir = ephemeral_dir.display().to_string();
+        let profile_config = config.profiles.get(profile);
+        match browser_type {
+            BrowserType::Chromium => {
+                for arg in [format!("--user-data-dir={}", temp_dir), "--no-first-run".to_string(), "--no-default-browser-check".to_string()] {
+                    cmd.arg(&arg);
+                    args.push(arg);
+                }
+            }
+            BrowserType::Firefox => {
+                if firefox_app && !dry_run && let Err(e) = firefox::prepare_app_profile(ephemeral_dir) {
+                    eprintln!("Failed to prepare temporary app profile: {}", e);
+                }
+                firefox_profile_dir = Some(ephemeral_dir.clone());
+                // -no-remote starts a separate instance we can wait for
+                for arg in ["-profile".to_string(), temp_dir.clone(), "-no-remote".to_string()] {
+                    cmd.arg(&arg);
+                    args.push(arg);
+                }
+            }
+        }
+        let cli_flags = profile_config.and_then(|p| p.cli_flags.as_deref()).unwrap_or_default();
+        push_profile_flags(&mut cmd, &mut args, browser_type, cli_flags, verbose);
+        user_data_dir_used = Some(format!("{} (temporary)", temp_dir));
+    } else if firefox_app {
+        let profile_config = config.profiles.get(profile);
+        let app_dir = profile_config
+            .and_then(|p| p.firefox_app_profile_dir.as_ref())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| firefox::default_app_profile_dir(sandbox.as_ref(), profile));
+        if !dry_run && let Err(e) = firefox::prepare_app_profile(&app_dir) {
+            eprintln!("Failed to prepare Firefox app profile {}: {}", app_dir.display(), e);
+            return;
+        }
+        firefox_profile_dir = Some(app_dir.clone());
+        let app_dir = app_dir.display().to_string();
+        for arg in ["-profile".to_string(), app_dir.clone()] {
+            cmd.arg(&arg);
+            args.push(arg);
+        }
+        let cli_flags = profile_config.and_then(|p| p.cli_flags.as_deref()).unwrap_or_default();
+        push_profile_flags(&mut cmd, &mut args, browser_type, cli_flags, verbose);
+        user_data_dir_used = Some(format!("{} (app profile)", app_dir));
+    } else if let Some(profile_config) = config.profiles.get(profile) {
+        if profile_config.create_if_missing.unwrap_or(false) && !dry_run {
+            match bootstrap::create_profile(profile, profile_config, browser_type, command, verbose) {
+                Ok(true) => println!("Created missing profile '{}'", profile),
+                Ok(false) => {}
+                Err(e) => eprintln!("Failed to create profile '{}': {}", profile, e),
+            }
+        } else if verbose && !bootstrap::profile_exists(profile, profile_config, browser_type, command) {
+            println!("⚠️  Profile '{}' does not exist yet (set create_if_missing: true or run `brolaunch profile create {}`)", profile, profile);
+        }
+
         match browser_type {
             BrowserType::Chromium => {
                 if let Some(user_data_dir) = &profile_config.user_data_dir {
@@ -197,19 +699,10 @@ fn launch_browser(binary: &str, browser_type: &BrowserType, profile: &str, url:
                     user_data_dir_used = Some(user_data_dir.clone());
                 }
                 
-                if let Some(cli_flags) = &profile_config.cli_flags {
-                    let session_type = DesktopEnvironment::detect_session_type();
-                    for flag in cli_flags {
-                        if should_include_flag(flag, &session_type) {
-                            cmd.arg(flag);
-                            args.push(flag.clone());
-                        } else if verbose {
-                            println!("🚫 Skipping flag '{}' (not compatible with {:?})", flag, session_type);
-                        }
-                    }
-                }
+                push_profile_flags(&mut cmd, &mut args, browser_type, profile_config.cli_flags.as_deref().unwrap_or_default(), verbose);
                 
-                let profile_arg = format!("--profile-directory={}", profile);
+                let profile_directory = resolve_chromium_profile_directory(command, profile, Some(profile_config), verbose);
+                let profile_arg = format!("--profile-directory={}", profile_directory);
                 cmd.arg(&profile_arg);
                 args.push(profile_arg);
             }
@@ -220,80 +713,125 @@ fn launch_browser(binary: &str, browser_type: &BrowserType, profile: &str, url:
                     args.push("-profile".to_string());
                     args.push(user_data_dir.clone());
                     user_data_dir_used = Some(user_data_dir.clone());
+                    firefox_profile_dir = Some(PathBuf::from(user_data_dir));
                 } else {
+                    let profile_name = resolve_firefox_profile_name(command, profile, Some(profile_config), verbose);
+                    firefox_profile_dir = discovery::firefox_profiles_dir(command)
+                        .and_then(|dir| discovery::read_firefox_profiles(&dir).ok())
+                        .and_then(|profiles| discovery::find_profile(&profiles, &profile_name).map(|p| PathBuf::from(&p.directory)));
                     cmd.arg("-P");
-                    cmd.arg(profile);
+                    cmd.arg(&profile_name);
                     args.push("-P".to_string());
-                    args.push(profile.to_string());
-                }
-                
-                if let Some(cli_flags) = &profile_config.cli_flags {
-                    for flag in cli_flags {
-                        cmd.arg(flag);
-                        args.push(flag.clone());
-                    }
+                    args.push(profile_name);
                 }
+
+
+                push_profile_flags(&mut cmd, &mut args, browser_type, profile_config.cli_flags.as_deref().unwrap_or_default(), verbose);
             }
         }
     } else {
         match browser_type {
             BrowserType::Chromium => {
-                let profile_arg = format!("--profile-directory={}", profile);
+                let profile_directory = resolve_chromium_profile_directory(command, profile, None, verbose);
+                let profile_arg = format!("--profile-directory={}", profile_directory);
                 cmd.arg(&profile_arg);
                 args.push(profile_arg);
             }
             BrowserType::Firefox => {
+                let profile_name = resolve_firefox_profile_name(command, profile, None, verbose);
                 cmd.arg("-P");
-                cmd.arg(profile);
+                cmd.arg(&profile_name);
                 args.push("-P".to_string());
-                args.push(profile.to_string());
+                args.push(profile_name);
             }
         }
     }
-    
-    if let Some(url) = url {
+
+    if let Some(profile_config) = config.profiles.get(profile) {
         match browser_type {
             BrowserType::Chromium => {
-                if app_mode {
-                    let app_arg = format!("--app={}", url);
-                    cmd.arg(&app_arg);
-                    args.push(app_arg);
-                } else {
-                    if !existing {
-                        cmd.arg("--new-window");
-                        args.push("--new-window".to_string());
+                match profile_config.proxy.as_ref().map(ProxyConfig::chromium_flags).transpose() {
+                    Ok(proxy_flags) => {
+                        for flag in proxy_flags.into_iter().flatten() {
+                            cmd.arg(&flag);
+                            args.push(flag);
+                        }
                     }
-                    cmd.arg(url);
-                    args.push(url.to_string());
+                    Err(e) => eprintln!("Invalid proxy settings for '{}': {}", profile, e),
+                }
+                let extension_flags = extensions::chromium_flags(
+                    profile_config.extensions.as_deref().unwrap_or_default(),
+                    profile_config.disable_extensions_except.as_deref().unwrap_or_default(),
+                );
+                for flag in extension_flags {
+                    cmd.arg(&flag);
+                    args.push(flag);
+                }
+                if verbose && profile_config.prefs.as_ref().is_some_and(|prefs| !prefs.is_empty()) {
+                    println!("⚠️  prefs only apply to Firefox profiles; ignoring them for '{}'", profile);
                 }
             }
             BrowserType::Firefox => {
-                if app_mode && verbose {
-                    println!("⚠️  App mode not supported for Firefox, opening in new window");
+                if verbose && (profile_config.extensions.is_some() || profile_config.disable_extensions_except.is_some()) {
+                    println!("⚠️  extensions and disable_extensions_except only apply to Chromium; ignoring them for '{}'", profile);
                 }
-                if !existing {
-                    cmd.arg("-new-window");
-                    args.push("-new-window".to_string());
+                // Firefox has no proxy flags, so settings go into a managed block of the profile's user.js
+                match (firefox_managed_prefs(profile_config), &firefox_profile_dir) {
+                    (Err(e), _) => eprintln!("Invalid Firefox settings for '{}': {}", profile, e),
+                    (Ok(prefs), None) => {
+                        if verbose && !prefs.is_empty() {
+                            println!("⚠️  Could not find the Firefox profile directory of '{}'; {} managed prefs not written", profile, prefs.len());
+                        }
+                    }
+                    (Ok(prefs), Some(dir)) => {
+                        if dry_run {
+                            if verbose && !prefs.is_empty() {
+                                println!("🧩 Would write {} managed prefs to {}", prefs.len(), dir.join("user.js").display());
+                            }
+                        } else {
+                            match firefox::write_managed_prefs(dir, &prefs) {
+                                Ok(true) if verbose => println!("🧩 Updated managed prefs in {}", dir.join("user.js").display()),
+                                Ok(_) => {}
+                                Err(e) => eprintln!("Failed to write {}: {}", dir.join("user.js").display(), e),
+                            }
+                        }
+                    }
                 }
-                cmd.arg(url);
-                args.push(url.to_string());
             }
         }
-    } else {
-        match browser_type {
-            BrowserType::Chromium => {
-                if !existing {
-                    cmd.arg("--new-window");
-                    args.push("--new-window".to_string());
-                }
+    }
+
+    // Flags from the matched pattern entry go after the profile's own cli_flags
+    push_profile_flags(&mut cmd, &mut args, browser_type, &plan.cli_flags, verbose);
+    
+    match browser_type {
+        BrowserType::Chromium => {
+            for flag in window.chromium_flags() {
+                cmd.arg(&flag);
+                args.push(flag);
             }
-            BrowserType::Firefox => {
-                if !existing {
-                    cmd.arg("-new-window");
-                    args.push("-new-window".to_string());
-                }
+        }
+        BrowserType::Firefox => {
+            if window.start_fullscreen.unwrap_or(false) && verbose {
+                println!("⚠️  start_fullscreen is not supported for Firefox");
+            }
+        }
+    }
+
+    let url_arg = match (browser_type, url, container) {
+        // Containers need the extension installed in a normal window of the real profile
+        (BrowserType::Firefox, Some(url), Some(container)) if ephemeral || firefox_app || window_mode == WindowMode::Private => {
+            if verbose {
+                println!("⚠️  Container '{}' ignored for private, app and temporary windows", container);
             }
+            Some(url.to_string())
         }
+        (BrowserType::Firefox, Some(url), Some(container)) => Some(firefox::container_url(container, url)),
+        (_, url, _) => url.map(str::to_string),
+    };
+    for arg in window_mode_args(browser_type, window_mode, kiosk, url_arg) {
+        cmd.arg(&arg);
+        args.push(arg);
     }
     
     if verbose {
@@ -304,22 +842,63 @@ fn launch_browser(binary: &str, browser_type: &BrowserType, profile: &str, url:
         } else {
             println!("📁 User data directory: default");
         }
-        if app_mode && *browser_type == BrowserType::Chromium {
-            println!("📱 Mode: App window");
-        } else if existing {
-            println!("🪟 Mode: Existing browser window");
-        } else {
-            println!("🪟 Mode: New browser window");
+        if ephemeral {
+            println!("🗑️  Ephemeral: profile is deleted when the browser exits");
+        }
+        if let Some(container) = container {
+            match browser_type {
+                BrowserType::Firefox if !ephemeral && !firefox_app && window_mode != WindowMode::Private => {
+                    println!("📦 Container: {}", container)
+                }
+                BrowserType::Firefox => {}
+                BrowserType::Chromium => println!("⚠️  Container '{}' ignored (Firefox only)", container),
+            }
+        }
+        match window_mode {
+            WindowMode::App if kiosk => {
+                println!("📱 Mode: App window (kiosk)")
+            }
+            WindowMode::App if url.is_some() => println!("📱 Mode: App window"),
+            WindowMode::Private => println!("🕶️  Mode: Private window"),
+            WindowMode::ExistingWindow => println!("🪟 Mode: Existing browser window"),
+            _ => println!("🪟 Mode: New browser window"),
         }
-        println!("🚀 Executing: {} {}", binary, args.join(" "));
+        if let Some(env) = env {
+            for (name, value) in env {
+                println!("🌱 Environment: {}={}", name, value);
+            }
+        }
+        if !wrapper.is_empty() {
+            println!("🧰 Wrapper: {}", wrapper.join(" "));
+        }
+        println!("🚀 Executing: {} {}", full_command.iter().map(|arg| arg.as_str()).collect::<Vec<_>>().join(" "), args.join(" "));
+    }
+
+    if dry_run {
+        println!("{}", dry_run_command_line(env, &full_command, &args));
+        return;
     }
     
-    let status = cmd.status();
+    if window.has_geometry() && (is_hyprland() || is_sway()) {
+        match window_class_pattern(browser_type, url, window_mode) {
+            Some(class_pattern) => apply_window_geometry_rules(window, &class_pattern, verbose),
+            None if verbose => println!("⚠️  Not floating the window: only Chromium app windows have a class of their own"),
+            None => {}
+        }
+    }
+
     let browser_name = match browser_type {
         BrowserType::Chromium => "Chromium",
         BrowserType::Firefox => "Firefox",
     };
 
+    if let Some(ephemeral_profile) = ephemeral_profile {
+        supervise_ephemeral_browser(cmd, ephemeral_profile, browser_name, plan, browser_type, config, verbose);
+        return;
+    }
+
+    let status = cmd.status();
+
     match status {
         Ok(status) if status.success() => {
             if url.is_some() {
@@ -329,7 +908,7 @@ fn launch_browser(binary: &str, browser_type: &BrowserType, profile: &str, url:
             }
             // Apply Hyprland window rules after successful launch
             if let Some(profile_config) = config.profiles.get(profile) {
-                apply_hyprland_rules(profile_config, browser_type, verbose);
+                apply_hyprland_rules(profile_config, plan.workspace.as_deref(), browser_type, verbose);
             }
         },
         Ok(status) => eprintln!("{} exited with status: {}", browser_name, status),
@@ -337,10 +916,156 @@ fn launch_browser(binary: &str, browser_type: &BrowserType, profile: &str, url:
     }
 }
 
+/// Runs a browser on a throwaway profile and deletes the profile once the browser exits
+fn supervise_ephemeral_browser(
+    mut cmd: Command,
+    ephemeral_profile: EphemeralProfile,
+    browser_name: &str,
+    plan: &LaunchPlan,
+    browser_type: &BrowserType,
+    config: &Config,
+    verbose: bool,
+) {
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to launch {}: {}", browser_name, e);
+            return;
+        }
+    };
+
+    println!("{} launched with temporary profile based on '{}'", browser_name, plan.profile);
+    if let Some(profile_config) = config.profiles.get(&plan.profile) {
+        apply_hyprland_rules(profile_config, plan.workspace.as_deref(), browser_type, verbose);
+    }
+
+    match child.wait() {
+        Ok(status) if !status.success() => eprintln!("{} exited with status: {}", browser_name, status),
+        Err(e) => eprintln!("Failed to wait for {}: {}", browser_name, e),
+        _ => {}
+    }
+
+    let temp_dir = ephemeral_profile.path().display().to_string();
+    drop(ephemeral_profile);
+    if verbose {
+        println!("🧹 Removed temporary profile {}", temp_dir);
+    }
+}
+
 fn get_available_profiles(config: &Config) -> Vec<String> {
     config.profiles.keys().cloned().collect()
 }
 
+fn get_profile_previews(config: &Config) -> HashMap<String, Vec<String>> {
+    config
+        .profiles
+        .iter()
+        .map(|(name, profile_config)| {
+            let mut lines = vec![format!(
+                "user_data_dir: {}",
+                profile_config.user_data_dir.as_deref().unwrap_or("default")
+            )];
+            if let Some(extends) = &profile_config.extends {
+                lines.push(format!("extends: {}", extends));
+            }
+            if let Some(container) = &profile_config.container {
+                lines.push(format!("container: {}", container));
+            }
+            for (label, patterns) in [
+                ("patterns", &profile_config.patterns),
+                ("app_patterns", &profile_config.app_patterns),
+                ("private_patterns", &profile_config.private_patterns),
+            ] {
+                if let Some(patterns) = patterns.as_ref().filter(|p| !p.is_empty()) {
+                    lines.push(format!("{}:", label));
+                    lines.extend(patterns.iter().map(|entry| format!("  {}", entry.pattern())));
+                }
+            }
+            (name.clone(), lines)
+        })
+        .collect()
+}
+
+
+fn run_profile_command(matches: &clap::ArgMatches, config: &Config, browser_type: &BrowserType, command: &[String], verbose: bool) {
+    let Some(("create", create_matches)) = matches.subcommand() else {
+        unreachable!("profile subcommand is required");
+    };
+    let name = create_matches.get_one::<String>("name").expect("name is required");
+
+    let Some((name, profile_config)) = config
+        .profiles
+        .iter()
+        .find(|(profile, _)| profile.eq_ignore_ascii_case(name))
+    else {
+        eprintln!("'{}' is not a configured profile", name);
+        eprintln!("Available profiles: {}", get_available_profiles(config).join(", "));
+        return;
+    };
+
+    match bootstrap::create_profile(name, profile_config, browser_type, command, verbose) {
+        Ok(true) => println!("Created profile '{}'", name),
+        Ok(false) => println!("Profile '{}' already exists", name),
+        Err(e) => eprintln!("Failed to create profile '{}': {}", name, e),
+    }
+}
+
+fn run_init_command(matches: &clap::ArgMatches) {
+    let requested = matches.get_one::<String>("browser").map(|browser| match browser.as_str() {
+        "firefox" => BrowserType::Firefox,
+        _ => BrowserType::Chromium,
+    });
+
+    let to_stdout = matches.get_flag("stdout");
+    let browsers = init::detect_browsers();
+    for browser in &browsers {
+        let names: Vec<&str> = browser.profiles.iter().map(|p| p.name.as_str()).collect();
+        let found = format!("🔎 Found {} ({}): profiles [{}]", browser.name, browser.command.join(" "), names.join(", "));
+        // Keep stdout clean for `brolaunch init --stdout > config.yaml`
+        if to_stdout {
+            eprintln!("{}", found);
+        } else {
+            println!("{}", found);
+        }
+    }
+
+    let Some(browser) = init::select_browser(browsers, requested.as_ref()) else {
+        eprintln!("No supported browser found. Install Chromium, Chrome, Brave, Vivaldi, Edge or Firefox.");
+        return;
+    };
+    let yaml = init::generate_config(&browser);
+
+    if to_stdout {
+        print!("{}", yaml);
+        return;
+    }
+
+    let output = match matches.get_one::<String>("output") {
+        Some(path) => PathBuf::from(path),
+        None => match dirs::config_dir() {
+            Some(config_dir) => config_dir.join("brolaunch.yaml"),
+            None => {
+                eprintln!("Could not determine the config directory; use --output or --stdout");
+                return;
+            }
+        },
+    };
+
+    if output.exists() && !matches.get_flag("force") {
+        eprintln!("{} already exists; use --force to overwrite or --stdout to print", output.display());
+        return;
+    }
+    if let Some(parent) = output.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        eprintln!("Failed to create {}: {}", parent.display(), e);
+        return;
+    }
+    match std::fs::write(&output, yaml) {
+        Ok(()) => println!("Wrote {} config with {} profiles to {}", browser.name, browser.profiles.len(), output.display()),
+        Err(e) => eprintln!("Failed to write {}: {}", output.display(), e),
+    }
+}
 
 fn main() {
     let matches = ClapCommand::new("brolaunch")
@@ -358,6 +1083,7 @@ fn main() {
                 .long("config")
                 .value_name("FILE")
                 .help("Path to config file")
+                .global(true)
                 .action(clap::ArgAction::Set),
         )
         .arg(
@@ -365,6 +1091,7 @@ fn main() {
                 .short('v')
                 .long("verbose")
                 .help("Enable verbose logging")
+                .global(true)
                 .action(clap::ArgAction::SetTrue),
         )
         .arg(
@@ -373,6 +1100,13 @@ fn main() {
                 .help("Launch URL as an app (creates app-like window)")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("private")
+                .long("private")
+                .help("Open in an incognito/private window of the profile")
+                .conflicts_with("app")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("existing")
                 .short('e')
@@ -380,12 +1114,91 @@ fn main() {
                 .help("Open URL in newest existing window for the profile (if any)")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Print the browser command instead of running it")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("temp")
+                .long("temp")
+                .help("Open in a temporary profile that is deleted when the browser exits")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .subcommand(
+            ClapCommand::new("routes")
+                .about("Manage routes remembered from the profile chooser")
+                .subcommand_required(true)
+                .subcommand(ClapCommand::new("list").about("List learned routes"))
+                .subcommand(
+                    ClapCommand::new("forget")
+                        .about("Forget the learned route for a domain")
+                        .arg(Arg::new("domain").required(true).index(1)),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("init")
+                .about("Generate a starter config from installed browsers and their profiles")
+                .arg(
+                    Arg::new("browser")
+                        .long("browser")
+                        .value_name("BROWSER")
+                        .help("Browser to generate the config for")
+                        .value_parser(["chromium", "firefox"]),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Where to write the config (default: ~/.config/brolaunch.yaml)"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Overwrite an existing config file")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("stdout")
+                        .long("stdout")
+                        .help("Print the config instead of writing it")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("profile")
+                .about("Manage browser profiles for configured profiles")
+                .subcommand_required(true)
+                .subcommand(
+                    ClapCommand::new("create")
+                        .about("Create the browser profile (and seed it from template_dir)")
+                        .arg(Arg::new("name").required(true).index(1)),
+                ),
+        )
         .get_matches();
 
+    match matches.subcommand() {
+        Some(("routes", routes_matches)) => {
+            run_routes_command(routes_matches);
+            return;
+        }
+        Some(("init", init_matches)) => {
+            run_init_command(init_matches);
+            return;
+        }
+        _ => {}
+    }
+
     let config_path = matches.get_one::<String>("config").map(|s| s.as_str());
     let verbose = matches.get_flag("verbose");
     let app_mode = matches.get_flag("app");
     let existing = matches.get_flag("existing");
+    let private = matches.get_flag("private");
+    let cli_window_mode = WindowMode::NewWindow.with_cli_flags(app_mode, private, existing);
+    let temp = matches.get_flag("temp");
+    let dry_run = matches.get_flag("dry_run");
 
     // App mode requires a URL
     if app_mode && matches.get_one::<String>("url_or_profile").is_none() {
@@ -405,8 +1218,22 @@ fn main() {
         println!("📋 Using config file: {}", config_file.display());
     }
 
-    let config = match load_config(&config_file) {
-        Ok(cfg) => cfg,
+    let mut config = match load_config(&config_file) {
+        Ok((cfg, report)) => {
+            if verbose && report.files.len() > 1 {
+                println!("📋 Merged config files:");
+                for file in &report.files {
+                    println!("   {}", file.display());
+                }
+            }
+            if verbose && !report.inheritance.is_empty() {
+                println!("🧬 Profile inheritance:");
+                for chain in &report.inheritance {
+                    println!("   {}", chain);
+                }
+            }
+            cfg
+        }
         Err(e) => {
             eprintln!("Failed to load config from {:?}: {}", config_file, e);
             return;
@@ -418,24 +1245,42 @@ fn main() {
         return;
     }
 
-    let browser_type = config.browser_type.as_ref().unwrap_or(&BrowserType::Chromium);
-    let binary = match browser_type {
-        BrowserType::Chromium => config.chromium_binary.as_deref().unwrap_or("chromium"),
-        BrowserType::Firefox => config.firefox_binary.as_deref().unwrap_or("firefox"),
-    };
+    let browser_type = &config.browser_type.clone().unwrap_or(BrowserType::Chromium);
+    let command = browser_command(&config, browser_type);
+    let sandbox = Sandbox::from_command(&command);
+    if let Some(sandbox) = &sandbox {
+        sandbox::resolve_profile_dirs(&mut config, sandbox, verbose);
+    }
     
-    if verbose && *browser_type == BrowserType::Chromium && binary == "chromium" {
-        if std::path::Path::new("/usr/lib/chromium/chromium").exists() {
-            println!("⚠️  Warning: Using 'chromium' wrapper script which may cause duplicate flags");
-            println!("   Consider setting chromium_binary: \"/usr/lib/chromium/chromium\" in config");
-        }
+    if verbose && *browser_type == BrowserType::Chromium && command == ["chromium"]
+        && std::path::Path::new("/usr/lib/chromium/chromium").exists()
+    {
+        println!("⚠️  Warning: Using 'chromium' wrapper script which may cause duplicate flags");
+        println!("   Consider setting chromium_binary: \"/usr/lib/chromium/chromium\" in config");
+    }
+
+    if let Some(("profile", profile_matches)) = matches.subcommand() {
+        run_profile_command(profile_matches, &config, browser_type, &command, verbose);
+        return;
     }
     
     let available_profiles = get_available_profiles(&config);
 
+    let learned_routes_path = LearnedRoutes::default_path();
+    let mut learned_routes = match LearnedRoutes::load(&learned_routes_path) {
+        Ok(routes) => routes,
+        Err(e) => {
+            eprintln!("Failed to load learned routes from {:?}: {}", learned_routes_path, e);
+            LearnedRoutes::default()
+        }
+    };
+
     if verbose {
         println!("🌐 Browser type: {:?}", browser_type);
-        println!("🌐 Browser binary: {}", binary);
+        println!("🌐 Browser command: {}", command.join(" "));
+        if let Some(sandbox) = &sandbox {
+            println!("📦 Sandboxed browser: {}", sandbox);
+        }
         println!("👥 Available profiles: [{}]", available_profiles.join(", "));
         if let Some(default) = &config.default_profile {
             println!("⭐ Default profile: {}", default);
@@ -449,21 +1294,13 @@ fn main() {
             .filter_map(|p| p.app_patterns.as_ref())
             .map(|patterns| patterns.len())
             .sum();
-        if *browser_type == BrowserType::Chromium {
-            println!("🔍 URL patterns configured: {} regular, {} app patterns", total_patterns, total_app_patterns);
-        } else {
-            println!("🔍 URL patterns configured: {} (app mode not supported for Firefox)", total_patterns + total_app_patterns);
-        }
+        println!("🔍 URL patterns configured: {} regular, {} app patterns", total_patterns, total_app_patterns);
         
         for (profile, profile_config) in &config.profiles {
             let regular_count = profile_config.patterns.as_ref().map_or(0, |p| p.len());
             let app_count = profile_config.app_patterns.as_ref().map_or(0, |p| p.len());
             if regular_count > 0 || app_count > 0 {
-                if *browser_type == BrowserType::Chromium {
-                    println!("  {}: {} regular, {} app patterns", profile, regular_count, app_count);
-                } else {
-                    println!("  {}: {} patterns", profile, regular_count + app_count);
-                }
+                println!("  {}: {} regular, {} app patterns", profile, regular_count, app_count);
             }
         }
         println!();
@@ -483,38 +1320,104 @@ fn main() {
             if verbose {
                 println!("✅ Matched profile name: {}", profile);
             }
-            launch_browser(binary, browser_type, profile, None, &config, verbose, app_mode, existing);
+            launch_browser(&command, browser_type, &LaunchPlan::new(profile, None, cli_window_mode, temp), &config, verbose, dry_run);
         } else if url_or_profile.contains("://") || url_or_profile.contains(".") {
             // Treat as URL - check for regex pattern matches
             if verbose {
                 println!("🌐 Treating as URL, checking regex patterns...");
             }
+
+            let url = url_unwrap::unwrap_url(
+                url_or_profile,
+                config.unwrap_rules.as_deref().unwrap_or(&[]),
+                config.unwrap_redirects.unwrap_or(true),
+                verbose,
+            );
+            let url_or_profile = &url;
             
-            if let Some(profile_match) = match_profile(url_or_profile, &config) {
-                let final_app_mode = app_mode || profile_match.app_mode;
+            if let Some(mut plan) = match_profile(url_or_profile, &config, &learned_routes, &Zoned::now()) {
+                let pattern_mode = plan.window_mode;
+                plan.window_mode = pattern_mode.with_cli_flags(app_mode, private, existing);
                 if verbose {
-                    println!("✅ URL matched regex pattern for profile: {}", profile_match.profile);
-                    if profile_match.app_mode && *browser_type == BrowserType::Chromium {
+                    if plan.learned {
+                        println!("✅ URL matched learned route for profile: {}", plan.profile);
+                    } else {
+                        println!("✅ URL matched regex pattern for profile: {}", plan.profile);
+                    }
+                    if pattern_mode == WindowMode::App {
                         println!("📱 Pattern configured for app mode");
                     }
-                    if app_mode && !profile_match.app_mode && *browser_type == BrowserType::Chromium {
-                        println!("🔧 CLI --app flag overriding pattern default");
+                    if pattern_mode == WindowMode::Private {
+                        println!("🕶️  Pattern configured for a private window");
+                    }
+                    if plan.window_mode != pattern_mode && (app_mode || private) {
+                        println!("🔧 CLI {} flag overriding pattern default", if private { "--private" } else { "--app" });
+                    }
+                    if plan.ephemeral {
+                        println!("🗑️  Pattern configured for an ephemeral profile");
+                    }
+                    if plan.url.as_deref() != Some(url_or_profile.as_str()) {
+                        println!("✏️  Pattern rewrote URL to: {}", plan.url.as_deref().unwrap_or_default());
                     }
                 }
-                launch_browser(binary, browser_type, &profile_match.profile, Some(url_or_profile), &config, verbose, final_app_mode, existing);
+                plan.ephemeral |= temp;
+                launch_browser(&command, browser_type, &plan, &config, verbose, dry_run);
             } else {
                 if verbose {
                     println!("❌ No regex patterns matched, showing profile chooser");
                 }
                 println!("No profile matched for URL: {}", url_or_profile);
                 
-                if let Some(selected_profile) = desktop_dialog::show_profile_chooser_with_debug(&available_profiles, verbose) {
-                    if verbose {
-                        println!("👆 User selected profile: {}", selected_profile);
+                let domain = routes::domain_of(url_or_profile);
+                match desktop_dialog::show_profile_chooser_with_debug(
+                    &available_profiles,
+                    &get_profile_previews(&config),
+                    url_or_profile,
+                    domain.as_deref(),
+                    &config.chooser.clone().unwrap_or_default(),
+                    config.chooser_timeout.map(Duration::from_secs),
+                    verbose,
+                ) {
+                    Some(ChooserSelection::Open { profile, mode, remember }) => {
+                        if verbose {
+                            println!("👆 User selected profile: {} ({:?})", profile, mode);
+                        }
+                        if remember && let Some(domain) = &domain {
+                            if dry_run {
+                                println!("Would remember: {} → {} ({})", domain, profile, learned_routes.path().display());
+                            } else {
+                                learned_routes.remember(domain, &profile);
+                                match learned_routes.save() {
+                                    Ok(()) => println!("Remembered: {} → {} ({})", domain, profile, learned_routes.path().display()),
+                                    Err(e) => eprintln!("Failed to save learned route: {}", e),
+                                }
+                            }
+                        }
+                        let window_mode = match mode {
+                            OpenMode::Default => cli_window_mode,
+                            OpenMode::App => WindowMode::App,
+                            OpenMode::Private => WindowMode::Private,
+                        };
+                        launch_browser(&command, browser_type, &LaunchPlan::new(&profile, Some(url_or_profile), window_mode, temp), &config, verbose, dry_run);
                     }
-                    launch_browser(binary, browser_type, &selected_profile, Some(url_or_profile), &config, verbose, app_mode, existing);
-                } else {
-                    println!("No profile selected. Exiting.");
+                    Some(ChooserSelection::CopyUrlAndCancel) => {
+                        if desktop_dialog::copy_to_clipboard(url_or_profile) {
+                            println!("📋 Copied URL to clipboard: {}", url_or_profile);
+                        } else {
+                            eprintln!("Failed to copy URL to clipboard (install wl-clipboard, xclip or xsel)");
+                        }
+                    }
+                    Some(ChooserSelection::TimedOut) => match &config.fallback_profile {
+                        Some(fallback_profile) if config.profiles.contains_key(fallback_profile) => {
+                            println!("⏱️  Chooser timed out, using fallback profile: {}", fallback_profile);
+                            launch_browser(&command, browser_type, &LaunchPlan::new(fallback_profile, Some(url_or_profile), cli_window_mode, temp), &config, verbose, dry_run);
+                        }
+                        Some(fallback_profile) => {
+                            eprintln!("Chooser timed out and fallback_profile '{}' is not a configured profile", fallback_profile);
+                        }
+                        None => println!("Chooser timed out. Exiting."),
+                    },
+                    None => println!("No profile selected. Exiting."),
                 }
             }
         } else {
@@ -535,7 +1438,7 @@ fn main() {
             if verbose {
                 println!("✅ Using default profile: {}", default_profile);
             }
-            launch_browser(binary, browser_type, default_profile, None, &config, verbose, app_mode, existing);
+            launch_browser(&command, browser_type, &LaunchPlan::new(default_profile, None, cli_window_mode, temp), &config, verbose, dry_run);
         } else {
             if verbose {
                 println!("❌ No default profile configured");
@@ -546,3 +1449,139 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dry_run_command_line_quotes_env_and_wrapper() {
+        let env = BTreeMap::from([("LANG".to_string(), "de_DE.UTF-8".to_string()), ("GREETING".to_string(), "hi there".to_string())]);
+        let command = ["firejail", "--private", "chromium"].map(String::from);
+        let args = vec!["--profile-directory=Client A".to_string(), "https://example.com/?a=1&b=2".to_string()];
+        assert_eq!(
+            dry_run_command_line(Some(&env), &command.iter().collect::<Vec<_>>(), &args),
+            "GREETING='hi there' LANG=de_DE.UTF-8 firejail --private chromium '--profile-directory=Client A' 'https://example.com/?a=1&b=2'"
+        );
+    }
+
+    #[test]
+    fn test_private_window_command_lines() {
+        let url = Some("https://bank.example.com/".to_string());
+        let chromium = ["chromium".to_string()];
+        let args = window_mode_args(&BrowserType::Chromium, WindowMode::Private, false, url.clone());
+        assert_eq!(
+            dry_run_command_line(None, &chromium.iter().collect::<Vec<_>>(), &args),
+            "chromium --incognito https://bank.example.com/"
+        );
+        let firefox = ["firefox".to_string()];
+        // Private wins over kiosk for Firefox
+        let args = window_mode_args(&BrowserType::Firefox, WindowMode::Private, true, url.clone());
+        assert_eq!(
+            dry_run_command_line(None, &firefox.iter().collect::<Vec<_>>(), &args),
+            "firefox -private-window https://bank.example.com/"
+        );
+        assert_eq!(
+            window_mode_args(&BrowserType::Chromium, WindowMode::App, false, url),
+            vec!["--app=https://bank.example.com/"]
+        );
+    }
+
+    #[test]
+    fn test_window_class_pattern_only_for_app_windows() {
+        let url = Some("https://app.example.com/inbox");
+        assert_eq!(
+            window_class_pattern(&BrowserType::Chromium, url, WindowMode::App).as_deref(),
+            Some("^(chrome-app\\.example\\.com__.*)$")
+        );
+        assert_eq!(window_class_pattern(&BrowserType::Chromium, url, WindowMode::NewWindow), None);
+        assert_eq!(window_class_pattern(&BrowserType::Firefox, url, WindowMode::App), None);
+    }
+
+    fn at(timestamp: &str) -> Zoned {
+        timestamp.parse().unwrap()
+    }
+
+    const TIMED_CONFIG: &str = r#"
+profiles:
+  Personal:
+    patterns: ["youtube\\.com"]
+  Work:
+    when: {days: [mon-fri], time: "09:00-17:00", timezone: UTC}
+    patterns: ["youtube\\.com"]
+"#;
+
+    #[test]
+    fn test_match_profile_prefers_when_gated_match() {
+        let config: Config = serde_yaml::from_str(TIMED_CONFIG).unwrap();
+        let routes = LearnedRoutes::default();
+        let url = "https://youtube.com/watch?v=1";
+        // 2026-10-19 is a Monday, 2026-10-18 a Sunday
+        let monday = match_profile(url, &config, &routes, &at("2026-10-19T10:00[UTC]")).unwrap();
+        assert_eq!(monday.profile, "Work");
+        let sunday = match_profile(url, &config, &routes, &at("2026-10-18T10:00[UTC]")).unwrap();
+        assert_eq!(sunday.profile, "Personal");
+        let monday_evening = match_profile(url, &config, &routes, &at("2026-10-19T18:00[UTC]")).unwrap();
+        assert_eq!(monday_evening.profile, "Personal");
+    }
+
+    #[test]
+    fn test_match_profile_gated_entry_beats_earlier_ungated_list() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+profiles:
+  Work:
+    app_patterns: ["mail\\.example\\.com"]
+    patterns:
+      - pattern: "mail\\.example\\.com"
+        when: {days: [mon-fri], timezone: UTC}
+"#,
+        )
+        .unwrap();
+        let routes = LearnedRoutes::default();
+        let url = "https://mail.example.com/";
+        let monday = match_profile(url, &config, &routes, &at("2026-10-19T10:00[UTC]")).unwrap();
+        assert_eq!(monday.window_mode, WindowMode::NewWindow);
+        let sunday = match_profile(url, &config, &routes, &at("2026-10-18T10:00[UTC]")).unwrap();
+        assert_eq!(sunday.window_mode, WindowMode::App);
+    }
+
+    #[test]
+    fn test_match_profile_private_patterns_and_cli_flags() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+profiles:
+  Personal:
+    app_mode: true
+    private_patterns: ["bank\\.example\\.com"]
+    patterns: ["example\\.com"]
+"#,
+        )
+        .unwrap();
+        let routes = LearnedRoutes::default();
+        let now = at("2026-10-19T10:00[UTC]");
+        let private = match_profile("https://bank.example.com/", &config, &routes, &now).unwrap();
+        assert_eq!(private.window_mode, WindowMode::Private);
+        let app = match_profile("https://example.com/", &config, &routes, &now).unwrap();
+        assert_eq!(app.window_mode, WindowMode::App);
+
+        // CLI flags override the pattern's mode, --private over --app; --existing only replaces a new window
+        assert_eq!(private.window_mode.with_cli_flags(true, false, false), WindowMode::App);
+        assert_eq!(app.window_mode.with_cli_flags(true, true, false), WindowMode::Private);
+        assert_eq!(app.window_mode.with_cli_flags(false, false, true), WindowMode::App);
+        assert_eq!(WindowMode::NewWindow.with_cli_flags(false, false, true), WindowMode::ExistingWindow);
+    }
+
+    #[test]
+    fn test_match_profile_skips_learned_routes_outside_when() {
+        let config: Config = serde_yaml::from_str(TIMED_CONFIG).unwrap();
+        let mut routes = LearnedRoutes::default();
+        routes.remember("example.com", "Work");
+        let url = "https://example.com/";
+
+        let plan = match_profile(url, &config, &routes, &at("2026-10-19T10:00[UTC]")).unwrap();
+        assert_eq!(plan.profile, "Work");
+        assert!(plan.learned);
+        assert!(match_profile(url, &config, &routes, &at("2026-10-18T10:00[UTC]")).is_none());
+    }
+}
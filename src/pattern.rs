@@ -0,0 +1,209 @@
+use crate::WindowMode;
+use crate::schedule::Schedule;
+use regex::Captures;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// One entry of a profile's pattern lists: either a bare regex or a map
+/// with the regex under `pattern` plus options for URLs it matches
+#[derive(Debug, Clone)]
+pub enum PatternEntry {
+    Regex(String),
+    Detailed(PatternOptions),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternOptions {
+    pub pattern: String,
+    /// Overrides the window mode implied by the list the entry is in
+    pub mode: Option<WindowMode>,
+    /// Shorthand for `mode: private`
+    pub private: Option<bool>,
+    pub ephemeral: Option<bool>,
+    /// Appended after the profile's own cli_flags
+    pub cli_flags: Option<Vec<String>>,
+    /// Hyprland workspace, overriding the profile's hyprland_workspace
+    pub workspace: Option<String>,
+    pub container: Option<String>,
+    /// URL to open instead, with `$1`/`${name}` replaced by the pattern's captures
+    pub rewrite: Option<String>,
+    /// Only match while the condition holds
+    pub when: Option<Schedule>,
+    #[serde(flatten)]
+    pub window: WindowOptions,
+}
+
+/// Window geometry for the launched window
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct WindowOptions {
+    pub window_size: Option<WindowPair>,
+    pub window_position: Option<WindowPair>,
+    pub kiosk: Option<bool>,
+    pub start_fullscreen: Option<bool>,
+}
+
+/// Two numbers written as `1280x800` or `1280,800`
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct WindowPair(pub i32, pub i32);
+
+impl TryFrom<String> for WindowPair {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (first, second) = value
+            .split_once(['x', 'X', ','])
+            .ok_or_else(|| format!("expected WIDTHxHEIGHT or X,Y, got '{}'", value))?;
+        let parse = |n: &str| n.trim().parse::<i32>().map_err(|_| format!("invalid number '{}' in '{}'", n.trim(), value));
+        Ok(WindowPair(parse(first)?, parse(second)?))
+    }
+}
+
+impl PatternEntry {
+    pub fn pattern(&self) -> &str {
+        match self {
+            PatternEntry::Regex(pattern) => pattern,
+            PatternEntry::Detailed(options) => &options.pattern,
+        }
+    }
+
+    pub fn options(&self) -> Option<&PatternOptions> {
+        match self {
+            PatternEntry::Regex(_) => None,
+            PatternEntry::Detailed(options) => Some(options),
+        }
+    }
+
+    pub fn when(&self) -> Option<&Schedule> {
+        self.options().and_then(|options| options.when.as_ref())
+    }
+
+    pub fn window(&self) -> WindowOptions {
+        match self {
+            PatternEntry::Regex(_) => WindowOptions::default(),
+            PatternEntry::Detailed(options) => options.window.clone(),
+        }
+    }
+}
+
+impl PatternOptions {
+    pub fn window_mode(&self) -> Option<WindowMode> {
+        self.mode.or(self.private.filter(|private| *private).map(|_| WindowMode::Private))
+    }
+
+    pub fn rewrite_url(&self, captures: &Captures) -> Option<String> {
+        let rewrite = self.rewrite.as_ref()?;
+        let mut url = String::new();
+        captures.expand(rewrite, &mut url);
+        Some(url)
+    }
+}
+
+impl WindowOptions {
+    /// Whether the window should be floated and placed by the compositor
+    pub fn has_geometry(&self) -> bool {
+        self.window_size.is_some() || self.window_position.is_some()
+    }
+
+    pub fn chromium_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if let Some(WindowPair(width, height)) = self.window_size {
+            flags.push(format!("--window-size={},{}", width, height));
+        }
+        if let Some(WindowPair(x, y)) = self.window_position {
+            flags.push(format!("--window-position={},{}", x, y));
+        }
+        if self.kiosk.unwrap_or(false) {
+            flags.push("--kiosk".to_string());
+        }
+        if self.start_fullscreen.unwrap_or(false) {
+            flags.push("--start-fullscreen".to_string());
+        }
+        flags
+    }
+}
+
+// Hand-written so a typo inside a map entry reports the real field error
+// instead of serde's generic "did not match any variant" for untagged enums
+impl<'de> Deserialize<'de> for PatternEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = PatternEntry;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a regex string or a map with a `pattern` key")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<PatternEntry, E> {
+                Ok(PatternEntry::Regex(value.to_string()))
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<PatternEntry, M::Error> {
+                PatternOptions::deserialize(de::value::MapAccessDeserializer::new(map)).map(PatternEntry::Detailed)
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_entries_accept_strings_and_maps() {
+        let entries: Vec<PatternEntry> = serde_yaml::from_str(
+            r#"
+- "github\\.com"
+- pattern: "mail\\.google\\.com"
+  window_size: 1280x800
+  window_position: "0,40"
+  kiosk: true
+"#,
+        )
+        .unwrap();
+        assert_eq!(entries[0].pattern(), "github\\.com");
+        assert_eq!(entries[0].window(), WindowOptions::default());
+
+        let window = entries[1].window();
+        assert_eq!(window.window_size, Some(WindowPair(1280, 800)));
+        assert_eq!(
+            window.chromium_flags(),
+            vec!["--window-size=1280,800", "--window-position=0,40", "--kiosk"]
+        );
+    }
+
+    #[test]
+    fn test_pattern_launch_options() {
+        let entry: PatternEntry = serde_yaml::from_str(
+            r#"
+pattern: "^https://old\\.example\\.com/(?P<path>.*)"
+rewrite: "https://new.example.com/${path}"
+private: true
+cli_flags: ["--disable-extensions"]
+"#,
+        )
+        .unwrap();
+        let options = entry.options().unwrap();
+        assert_eq!(options.window_mode(), Some(WindowMode::Private));
+
+        let re = regex::Regex::new(entry.pattern()).unwrap();
+        let captures = re.captures("https://old.example.com/docs?a=1").unwrap();
+        assert_eq!(options.rewrite_url(&captures).as_deref(), Some("https://new.example.com/docs?a=1"));
+
+        let entry: PatternEntry = serde_yaml::from_str("pattern: x\nmode: existing\nprivate: true\n").unwrap();
+        assert_eq!(entry.options().unwrap().window_mode(), Some(WindowMode::ExistingWindow));
+    }
+
+    #[test]
+    fn test_invalid_pattern_options_are_rejected() {
+        let error = serde_yaml::from_str::<Vec<PatternEntry>>("- pattern: x\n  window_size: big\n").unwrap_err();
+        assert!(error.to_string().contains("WIDTHxHEIGHT"));
+        assert!(serde_yaml::from_str::<Vec<PatternEntry>>("- pattern: x\n  windw_size: 1x1\n").is_err());
+    }
+}
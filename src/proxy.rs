@@ -0,0 +1,194 @@
+use crate::firefox::PrefValue;
+use serde::Deserialize;
+use url::Url;
+
+/// Proxy settings for a profile's browser
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyConfig {
+    /// `http://host:port`, `https://...`, `socks4://...` or `socks5://...`
+    pub server: Option<String>,
+    /// Hosts that skip the proxy (`localhost`, `*.corp.example`, `10.0.0.0/8`)
+    pub bypass: Option<Vec<String>>,
+    /// Proxy auto-config script; cannot be combined with `server`
+    pub pac_url: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum ProxyKind {
+    Http,
+    Https,
+    Socks(i64),
+}
+
+#[derive(Debug, PartialEq)]
+struct ProxyServer {
+    kind: ProxyKind,
+    host: String,
+    port: i64,
+}
+
+fn parse_server(server: &str) -> Result<ProxyServer, String> {
+    // A bare host:port means an HTTP proxy, as in Chromium's --proxy-server
+    let with_scheme = if server.contains("://") { server.to_string() } else { format!("http://{}", server) };
+    let url = Url::parse(&with_scheme).map_err(|e| format!("invalid proxy server '{}': {}", server, e))?;
+    let (kind, default_port) = match url.scheme() {
+        "http" => (ProxyKind::Http, 80),
+        "https" => (ProxyKind::Https, 443),
+        "socks4" => (ProxyKind::Socks(4), 1080),
+        // Chromium reads `socks://` as SOCKS4 and rejects `socks5h://`, so both are normalized below
+        "socks" | "socks5" | "socks5h" => (ProxyKind::Socks(5), 1080),
+        scheme => return Err(format!("unsupported proxy scheme '{}' in '{}' (use http, https, socks4 or socks5)", scheme, server)),
+    };
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| format!("proxy server '{}' has no host", server))?;
+    Ok(ProxyServer {
+        kind,
+        host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+        port: url.port().map_or(default_port, i64::from),
+    })
+}
+
+impl ProxyServer {
+    /// Canonical `scheme://host:port` form, which Chromium parses the same way Firefox does
+    fn chromium_url(&self) -> String {
+        let scheme = match self.kind {
+            ProxyKind::Http => "http".to_string(),
+            ProxyKind::Https => "https".to_string(),
+            ProxyKind::Socks(version) => format!("socks{}", version),
+        };
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        format!("{}://{}:{}", scheme, host, self.port)
+    }
+}
+
+impl ProxyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.server.is_some() && self.pac_url.is_some() {
+            return Err("set either server or pac_url, not both".to_string());
+        }
+        if let Some(server) = &self.server {
+            parse_server(server)?;
+        }
+        if let Some(pac_url) = &self.pac_url {
+            Url::parse(pac_url).map_err(|e| format!("invalid pac_url '{}': {}", pac_url, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn chromium_flags(&self) -> Result<Vec<String>, String> {
+        let mut flags = Vec::new();
+        if let Some(pac_url) = &self.pac_url {
+            flags.push(format!("--proxy-pac-url={}", pac_url));
+        } else if let Some(server) = &self.server {
+            flags.push(format!("--proxy-server={}", parse_server(server)?.chromium_url()));
+        }
+        if let Some(bypass) = self.bypass.as_ref().filter(|bypass| !bypass.is_empty()) {
+            flags.push(format!("--proxy-bypass-list={}", bypass.join(";")));
+        }
+        Ok(flags)
+    }
+
+    /// `network.proxy.*` preferences for the profile's managed `user.js` block
+    pub fn firefox_prefs(&self) -> Result<Vec<(String, PrefValue)>, String> {
+        let mut prefs = Vec::new();
+        let mut pref = |name: &str, value: PrefValue| prefs.push((name.to_string(), value));
+
+        if let Some(pac_url) = &self.pac_url {
+            pref("network.proxy.type", PrefValue::Int(2));
+            pref("network.proxy.autoconfig_url", PrefValue::String(pac_url.clone()));
+        } else if let Some(server) = &self.server {
+            let server = parse_server(server)?;
+            pref("network.proxy.type", PrefValue::Int(1));
+            match server.kind {
+                ProxyKind::Http | ProxyKind::Https => {
+                    for scheme in ["http", "ssl"] {
+                        pref(&format!("network.proxy.{}", scheme), PrefValue::String(server.host.clone()));
+                        pref(&format!("network.proxy.{}_port", scheme), PrefValue::Int(server.port));
+                    }
+                }
+                ProxyKind::Socks(version) => {
+                    pref("network.proxy.socks", PrefValue::String(server.host.clone()));
+                    pref("network.proxy.socks_port", PrefValue::Int(server.port));
+                    pref("network.proxy.socks_version", PrefValue::Int(version));
+                    pref("network.proxy.socks_remote_dns", PrefValue::Bool(true));
+                }
+            }
+        }
+        if let Some(bypass) = self.bypass.as_ref().filter(|bypass| !bypass.is_empty()) {
+            pref("network.proxy.no_proxies_on", PrefValue::String(bypass.join(", ")));
+        }
+        Ok(prefs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_chromium_flags() {
+        let proxy: ProxyConfig = serde_yaml::from_str(
+            "server: \"http://proxy.corp:3128\"\nbypass: [\"localhost\", \"*.corp.example\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            proxy.chromium_flags().unwrap(),
+            vec!["--proxy-server=http://proxy.corp:3128", "--proxy-bypass-list=localhost;*.corp.example"]
+        );
+
+        // SOCKS aliases get the same meaning as for Firefox: SOCKS v5 on the default port
+        for server in ["socks://127.0.0.1", "socks5h://127.0.0.1:1080"] {
+            let socks = ProxyConfig {
+                server: Some(server.to_string()),
+                ..ProxyConfig::default()
+            };
+            assert_eq!(socks.chromium_flags().unwrap(), vec!["--proxy-server=socks5://127.0.0.1:1080"]);
+        }
+
+        let pac = ProxyConfig {
+            pac_url: Some("http://wpad.corp/proxy.pac".to_string()),
+            ..ProxyConfig::default()
+        };
+        assert_eq!(pac.chromium_flags().unwrap(), vec!["--proxy-pac-url=http://wpad.corp/proxy.pac"]);
+
+        let invalid = ProxyConfig {
+            server: Some("ftp://proxy.corp".to_string()),
+            ..ProxyConfig::default()
+        };
+        assert!(invalid.chromium_flags().is_err());
+    }
+
+    #[test]
+    fn test_proxy_server_and_pac_url_are_exclusive() {
+        let proxy: ProxyConfig =
+            serde_yaml::from_str("server: \"http://proxy.corp:3128\"\npac_url: \"http://wpad.corp/proxy.pac\"\n").unwrap();
+        assert!(proxy.validate().unwrap_err().contains("not both"));
+    }
+
+    #[test]
+    fn test_proxy_firefox_prefs() {
+        let proxy = ProxyConfig {
+            server: Some("socks5://127.0.0.1:1080".to_string()),
+            ..ProxyConfig::default()
+        };
+        let prefs = proxy.firefox_prefs().unwrap();
+        assert!(prefs.contains(&("network.proxy.type".to_string(), PrefValue::Int(1))));
+        assert!(prefs.contains(&("network.proxy.socks".to_string(), PrefValue::String("127.0.0.1".to_string()))));
+        assert!(prefs.contains(&("network.proxy.socks_version".to_string(), PrefValue::Int(5))));
+
+        let http = ProxyConfig {
+            server: Some("proxy.corp:3128".to_string()),
+            ..ProxyConfig::default()
+        };
+        assert!(http.firefox_prefs().unwrap().contains(&("network.proxy.ssl_port".to_string(), PrefValue::Int(3128))));
+
+        let invalid = ProxyConfig {
+            server: Some("ftp://proxy.corp".to_string()),
+            ..ProxyConfig::default()
+        };
+        assert!(invalid.validate().unwrap_err().contains("unsupported proxy scheme"));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Domain → profile decisions remembered from the profile chooser.
+/// Kept in its own file so the hand-written config is never rewritten.
+#[derive(Debug, Default)]
+pub struct LearnedRoutes {
+    path: PathBuf,
+    routes: BTreeMap<String, String>,
+}
+
+impl LearnedRoutes {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("brolaunch")
+            .join("routes.yaml")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let routes = if path.exists() {
+            let contents = fs::read_to_string(path)?;
+            serde_yaml::from_str::<Option<BTreeMap<String, String>>>(&contents)?.unwrap_or_default()
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(LearnedRoutes {
+            path: path.to_path_buf(),
+            routes,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut contents = String::from("# Routes learned from the brolaunch profile chooser.\n");
+        contents.push_str("# Remove entries with: brolaunch routes forget <domain>\n");
+        if !self.routes.is_empty() {
+            contents.push_str(&serde_yaml::to_string(&self.routes)?);
+        }
+        fs::write(&self.path, contents)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.routes.iter()
+    }
+
+    /// Finds the profile for a URL, matching the learned domain or any of its subdomains.
+    /// Routes to profiles rejected by `usable` are skipped.
+    pub fn lookup(&self, url: &str, usable: impl Fn(&str) -> bool) -> Option<&str> {
+        let host = domain_of(url)?;
+        self.routes
+            .iter()
+            .filter(|(domain, _)| host == **domain || host.ends_with(&format!(".{}", domain)))
+            .filter(|(_, profile)| usable(profile))
+            // Prefer the most specific domain when several match
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(_, profile)| profile.as_str())
+    }
+
+    pub fn remember(&mut self, domain: &str, profile: &str) {
+        self.routes.insert(domain.to_lowercase(), profile.to_string());
+    }
+
+    pub fn forget(&mut self, domain: &str) -> bool {
+        let domain = domain.to_lowercase();
+        let domain = domain.strip_prefix("www.").unwrap_or(&domain);
+        self.routes.remove(domain).is_some()
+    }
+}
+
+/// Host of a URL without a leading `www.`; bare `example.com/path` inputs are accepted.
+pub fn domain_of(url: &str) -> Option<String> {
+    let parsed = if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("https://{}", url))
+    };
+    let host = parsed.ok()?.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").map(str::to_string).unwrap_or(host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_of() {
+        assert_eq!(domain_of("https://www.Example.com/path?q=1"), Some("example.com".to_string()));
+        assert_eq!(domain_of("docs.rs/regex"), Some("docs.rs".to_string()));
+    }
+
+    #[test]
+    fn test_lookup_matches_subdomains() {
+        let mut routes = LearnedRoutes::default();
+        routes.remember("example.com", "Personal");
+        routes.remember("corp.example.com", "Work");
+
+        assert_eq!(routes.lookup("https://example.com/a", |_| true), Some("Personal"));
+        assert_eq!(routes.lookup("https://wiki.corp.example.com", |_| true), Some("Work"));
+        assert_eq!(routes.lookup("https://notexample.com", |_| true), None);
+        // An unusable profile falls back to the next most specific route
+        assert_eq!(routes.lookup("https://wiki.corp.example.com", |profile| profile != "Work"), Some("Personal"));
+
+        assert!(routes.forget("www.example.com"));
+        assert_eq!(routes.lookup("https://example.com/a", |_| true), None);
+    }
+}
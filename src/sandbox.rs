@@ -0,0 +1,218 @@
+use crate::{BrowserType, Config};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A browser packaged as a Flatpak or Snap, which sees a different filesystem than the host
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sandbox {
+    Flatpak { app_id: String },
+    Snap { name: String },
+}
+
+/// Browsers known to be packaged as Flatpaks, with their app ids
+pub const FLATPAK_BROWSERS: &[(&str, &str, BrowserType)] = &[
+    ("Chromium (Flatpak)", "org.chromium.Chromium", BrowserType::Chromium),
+    ("Google Chrome (Flatpak)", "com.google.Chrome", BrowserType::Chromium),
+    ("Brave (Flatpak)", "com.brave.Browser", BrowserType::Chromium),
+    ("Vivaldi (Flatpak)", "com.vivaldi.Vivaldi", BrowserType::Chromium),
+    ("Microsoft Edge (Flatpak)", "com.microsoft.Edge", BrowserType::Chromium),
+    ("Firefox (Flatpak)", "org.mozilla.firefox", BrowserType::Firefox),
+];
+
+/// Browsers known to be packaged as Snaps, by snap name
+pub const SNAP_BROWSERS: &[(&str, &str, BrowserType)] = &[
+    ("Chromium (Snap)", "chromium", BrowserType::Chromium),
+    ("Brave (Snap)", "brave", BrowserType::Chromium),
+    ("Firefox (Snap)", "firefox", BrowserType::Firefox),
+];
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Some(PathBuf::from(program));
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+// First argument after `run` that isn't an option, e.g. the app id in `flatpak run --branch=beta com.google.Chrome`
+fn run_target(args: &[String]) -> Option<String> {
+    let mut args = args.iter();
+    if args.next()? != "run" {
+        return None;
+    }
+    args.find(|arg| !arg.starts_with('-')).cloned()
+}
+
+impl Sandbox {
+    /// Recognises `flatpak run <app-id>`, `snap run <name>`, Flatpak export wrappers
+    /// and binaries that resolve to `/snap/bin/<name>`
+    pub fn from_command(command: &[String]) -> Option<Sandbox> {
+        let (program, args) = command.split_first()?;
+        let program_name = Path::new(program).file_name()?.to_string_lossy().into_owned();
+        match program_name.as_str() {
+            "flatpak" => return run_target(args).map(|app_id| Sandbox::Flatpak { app_id }),
+            "snap" => return run_target(args).map(|name| Sandbox::Snap { name }),
+            _ => {}
+        }
+
+        let resolved = find_in_path(program)?;
+        if resolved.parent().is_some_and(|dir| dir.ends_with("flatpak/exports/bin")) {
+            return Some(Sandbox::Flatpak { app_id: program_name });
+        }
+        if resolved.starts_with("/snap/bin") {
+            // Snap apps may be exposed as `<snap>.<app>`
+            let name = program_name.split('.').next().unwrap_or(&program_name).to_string();
+            return Some(Sandbox::Snap { name });
+        }
+        None
+    }
+
+    /// Per-app directory on the host that the sandboxed browser can always write to
+    pub fn data_root(&self) -> Option<PathBuf> {
+        let home = dirs::home_dir()?;
+        Some(match self {
+            Sandbox::Flatpak { app_id } => home.join(".var").join("app").join(app_id),
+            Sandbox::Snap { name } => home.join("snap").join(name).join("common"),
+        })
+    }
+
+    /// What the browser sees as `$XDG_CONFIG_HOME` (Chromium user data dirs live below it)
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        let root = self.data_root()?;
+        Some(match self {
+            Sandbox::Flatpak { .. } => root.join("config"),
+            Sandbox::Snap { .. } => root,
+        })
+    }
+
+    pub fn firefox_profiles_dir(&self) -> Option<PathBuf> {
+        Some(self.data_root()?.join(".mozilla").join("firefox"))
+    }
+
+    /// Relative directories are placed in the sandbox's own directory; absolute ones are kept
+    pub fn resolve_dir(&self, dir: &str) -> String {
+        match self.data_root() {
+            Some(root) if Path::new(dir).is_relative() => root.join(dir).display().to_string(),
+            _ => dir.to_string(),
+        }
+    }
+
+    /// Where temporary profiles go, since the sandbox has its own private /tmp
+    pub fn temp_dir(&self) -> Option<PathBuf> {
+        Some(self.data_root()?.join("tmp"))
+    }
+
+    /// Whether the sandbox can reach `path` without extra permissions
+    pub fn can_access(&self, path: &Path) -> bool {
+        match self {
+            Sandbox::Flatpak { .. } => self.data_root().is_some_and(|root| path.starts_with(root)),
+            // The home interface covers non-hidden files in $HOME
+            Sandbox::Snap { .. } => {
+                self.data_root().is_some_and(|root| path.starts_with(root))
+                    || dirs::home_dir().is_some_and(|home| {
+                        path.strip_prefix(home).is_ok_and(|relative| {
+                            relative.components().all(|c| !c.as_os_str().to_string_lossy().starts_with('.'))
+                        })
+                    })
+            }
+        }
+    }
+
+    /// How to let the sandbox use `path`, for warnings
+    pub fn access_hint(&self, path: &Path) -> String {
+        match self {
+            Sandbox::Flatpak { app_id } => format!("flatpak override --user --filesystem={} {}", path.display(), app_id),
+            Sandbox::Snap { .. } => "use a relative user_data_dir or a non-hidden directory in your home".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Sandbox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Sandbox::Flatpak { app_id } => write!(f, "Flatpak {}", app_id),
+            Sandbox::Snap { name } => write!(f, "Snap {}", name),
+        }
+    }
+}
+
+/// Places relative `user_data_dir`s and `firefox_app_profile_dir`s inside the sandbox
+/// and warns about directories the sandbox cannot reach
+pub fn resolve_profile_dirs(config: &mut Config, sandbox: &Sandbox, verbose: bool) {
+    for (name, profile) in config.profiles.iter_mut() {
+        for dir in [&mut profile.user_data_dir, &mut profile.firefox_app_profile_dir].into_iter().flatten() {
+            *dir = sandbox.resolve_dir(dir);
+            let path = Path::new(dir.as_str());
+            if verbose && !sandbox.can_access(path) {
+                println!("⚠️  Profile '{}': {} is not visible inside the {} sandbox", name, dir, sandbox);
+                println!("   To allow it: {}", sandbox.access_hint(path));
+            }
+        }
+    }
+}
+
+fn flatpak_installed(app_id: &str) -> bool {
+    let system = Path::new("/var/lib/flatpak/app").join(app_id);
+    let user = dirs::data_dir().map(|dir| dir.join("flatpak").join("app").join(app_id));
+    system.exists() || user.is_some_and(|dir| dir.exists())
+}
+
+/// Installed Flatpak and Snap browsers as (name, browser type, launch command)
+pub fn detect_installed() -> Vec<(&'static str, BrowserType, Vec<String>)> {
+    let flatpaks = FLATPAK_BROWSERS
+        .iter()
+        .filter(|(_, app_id, _)| flatpak_installed(app_id))
+        .map(|(name, app_id, browser_type)| {
+            (*name, browser_type.clone(), vec!["flatpak".to_string(), "run".to_string(), app_id.to_string()])
+        });
+    let snaps = SNAP_BROWSERS
+        .iter()
+        .filter(|(_, snap, _)| Path::new("/snap/bin").join(snap).exists())
+        .map(|(name, snap, browser_type)| (*name, browser_type.clone(), vec![format!("/snap/bin/{}", snap)]));
+    flatpaks.chain(snaps).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(words: &str) -> Vec<String> {
+        words.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_sandbox_from_command() {
+        assert_eq!(
+            Sandbox::from_command(&command("flatpak run --branch=stable com.google.Chrome")),
+            Some(Sandbox::Flatpak { app_id: "com.google.Chrome".to_string() })
+        );
+        assert_eq!(
+            Sandbox::from_command(&command("/snap/bin/chromium")),
+            Some(Sandbox::Snap { name: "chromium".to_string() })
+        );
+        assert_eq!(
+            Sandbox::from_command(&command("/var/lib/flatpak/exports/bin/org.mozilla.firefox")),
+            Some(Sandbox::Flatpak { app_id: "org.mozilla.firefox".to_string() })
+        );
+        assert_eq!(Sandbox::from_command(&command("/usr/lib/chromium/chromium")), None);
+        assert_eq!(Sandbox::from_command(&command("flatpak list")), None);
+    }
+
+    #[test]
+    fn test_sandbox_paths() {
+        let home = dirs::home_dir().unwrap();
+        let chrome = Sandbox::Flatpak { app_id: "com.google.Chrome".to_string() };
+        assert_eq!(chrome.config_dir().unwrap(), home.join(".var/app/com.google.Chrome/config"));
+        assert_eq!(
+            chrome.resolve_dir("brolaunch/work"),
+            home.join(".var/app/com.google.Chrome/brolaunch/work").display().to_string()
+        );
+        assert!(!chrome.can_access(&home.join(".config/brolaunch/work")));
+
+        let firefox = Sandbox::Snap { name: "firefox".to_string() };
+        assert_eq!(firefox.firefox_profiles_dir().unwrap(), home.join("snap/firefox/common/.mozilla/firefox"));
+        assert!(firefox.can_access(&home.join("Browsers/work")));
+        assert!(!firefox.can_access(&home.join(".config/work")));
+    }
+}
@@ -0,0 +1,169 @@
+use jiff::Zoned;
+use jiff::civil::Weekday;
+use jiff::tz::TimeZone;
+use serde::Deserialize;
+
+/// A `when:` condition: the weekdays and time of day a profile or pattern applies
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schedule {
+    /// `[mon, tue]`, `[mon-fri]`, ...; any day when unset
+    pub days: Option<Vec<DayRange>>,
+    /// `09:00-17:30`; ranges past midnight such as `22:00-06:00` wrap around
+    pub time: Option<TimeRange>,
+    /// IANA name such as `Europe/Berlin`; the system time zone when unset
+    pub timezone: Option<Timezone>,
+}
+
+/// One weekday or a range of them, such as `mon-fri`
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct DayRange(Weekday, Weekday);
+
+/// Minutes since midnight, start inclusive and end exclusive
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct TimeRange(u16, u16);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Timezone(TimeZone);
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("monday", Weekday::Monday),
+    ("tuesday", Weekday::Tuesday),
+    ("wednesday", Weekday::Wednesday),
+    ("thursday", Weekday::Thursday),
+    ("friday", Weekday::Friday),
+    ("saturday", Weekday::Saturday),
+    ("sunday", Weekday::Sunday),
+];
+
+// Full names or any prefix of at least three letters: `mon`, `tues`, `thursday`
+fn parse_weekday(name: &str) -> Result<Weekday, String> {
+    let name = name.trim().to_lowercase();
+    WEEKDAYS
+        .iter()
+        .find(|(full, _)| name.len() >= 3 && full.starts_with(&name))
+        .map(|(_, day)| *day)
+        .ok_or_else(|| format!("unknown weekday '{}' (use mon, tue, ... or monday, tuesday, ...)", name))
+}
+
+impl TryFrom<String> for DayRange {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.split_once('-') {
+            Some((first, last)) => Ok(DayRange(parse_weekday(first)?, parse_weekday(last)?)),
+            None => {
+                let day = parse_weekday(&value)?;
+                Ok(DayRange(day, day))
+            }
+        }
+    }
+}
+
+impl DayRange {
+    /// Whether `day` is in the range; `fri-mon` wraps over the weekend
+    fn contains(&self, day: Weekday) -> bool {
+        let offset = |day: Weekday| day.to_monday_zero_offset();
+        let (first, last, day) = (offset(self.0), offset(self.1), offset(day));
+        if first <= last { (first..=last).contains(&day) } else { day >= first || day <= last }
+    }
+}
+
+fn parse_time_of_day(value: &str) -> Result<u16, String> {
+    let (hours, minutes) = value.trim().split_once(':').unwrap_or((value.trim(), "0"));
+    let invalid = || format!("invalid time '{}' (use HH:MM)", value.trim());
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 || hours > 24 || (hours == 24 && minutes > 0) {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| format!("expected a time range like 09:00-17:30, got '{}'", value))?;
+        Ok(TimeRange(parse_time_of_day(start)?, parse_time_of_day(end)?))
+    }
+}
+
+impl TimeRange {
+    fn contains(&self, minute: u16) -> bool {
+        let TimeRange(start, end) = *self;
+        if start <= end { start <= minute && minute < end } else { minute >= start || minute < end }
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        TimeZone::get(&value)
+            .map(Timezone)
+            .map_err(|e| format!("unknown timezone '{}': {}", value, e))
+    }
+}
+
+impl Schedule {
+    /// Whether the condition holds at `now`, which callers pass in so matching can be tested
+    pub fn matches(&self, now: &Zoned) -> bool {
+        let now = match &self.timezone {
+            Some(Timezone(timezone)) => now.with_time_zone(timezone.clone()),
+            None => now.clone(),
+        };
+        let day_matches = self
+            .days
+            .as_ref()
+            .is_none_or(|days| days.iter().any(|range| range.contains(now.weekday())));
+        let minute = now.hour() as u16 * 60 + now.minute() as u16;
+        day_matches && self.time.is_none_or(|time| time.contains(minute))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> Zoned {
+        timestamp.parse().unwrap()
+    }
+
+    fn schedule(yaml: &str) -> Schedule {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_days_and_time() {
+        let work_hours = schedule("days: [mon-fri]\ntime: \"09:00-17:30\"\n");
+        // 2026-10-19 is a Monday
+        assert!(work_hours.matches(&at("2026-10-19T09:00[UTC]")));
+        assert!(!work_hours.matches(&at("2026-10-19T17:30[UTC]")));
+        assert!(!work_hours.matches(&at("2026-10-18T12:00[UTC]")));
+
+        let nights = schedule("days: [fri, saturday, sun]\ntime: \"22:00-06:00\"\n");
+        assert!(nights.matches(&at("2026-10-18T05:59[UTC]")));
+        assert!(!nights.matches(&at("2026-10-19T23:00[UTC]")));
+    }
+
+    #[test]
+    fn test_schedule_timezone() {
+        let berlin = schedule("time: \"09:00-17:00\"\ntimezone: Europe/Berlin\n");
+        // 08:30 UTC is 10:30 in Berlin (CEST)
+        assert!(berlin.matches(&at("2026-07-01T08:30[UTC]")));
+        assert!(!berlin.matches(&at("2026-07-01T15:30[UTC]")));
+    }
+
+    #[test]
+    fn test_invalid_schedules_are_rejected() {
+        assert!(serde_yaml::from_str::<Schedule>("days: [funday]\n").unwrap_err().to_string().contains("unknown weekday"));
+        assert!(serde_yaml::from_str::<Schedule>("time: \"25:00-26:00\"\n").is_err());
+        assert!(serde_yaml::from_str::<Schedule>("timezone: Mars/Olympus\n").is_err());
+    }
+}
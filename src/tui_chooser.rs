@@ -0,0 +1,219 @@
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq)]
+pub enum Choice {
+    /// Index into the items passed to `choose`
+    Selected(usize),
+    Cancelled,
+    TimedOut,
+}
+
+/// True when stdin/stdout are an interactive terminal capable of cursor control.
+pub fn is_supported() -> bool {
+    let dumb_term = std::env::var("TERM").map_or(true, |term| term.is_empty() || term == "dumb");
+    !dumb_term && io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+/// Subsequence fuzzy match; higher scores for consecutive and word-start hits.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut position = 0;
+
+    for query_char in query.to_lowercase().chars() {
+        let offset = candidate[position..].iter().position(|c| *c == query_char)?;
+        let index = position + offset;
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(index);
+        position = index + 1;
+    }
+
+    // Prefer shorter candidates when scores tie
+    Some(score * 100 - candidate.len() as i64)
+}
+
+fn filter_items(items: &[String], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(usize, i64)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| fuzzy_score(query, item).map(|score| (i, score)))
+        .collect();
+    if !query.is_empty() {
+        scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    }
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+/// Restores the terminal even if drawing fails half-way
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw(
+    out: &mut impl Write,
+    prompt: &str,
+    query: &str,
+    items: &[String],
+    previews: &[Vec<String>],
+    filtered: &[usize],
+    selected: usize,
+) -> io::Result<()> {
+    // Some ptys report 0x0; assume a classic terminal size then
+    let (width, height) = match terminal::size()? {
+        (0, _) | (_, 0) => (80, 24),
+        (width, height) => (width as usize, height as usize),
+    };
+
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0), Print(truncate(prompt, width)))?;
+    queue!(out, MoveTo(0, 1), Print(truncate(&format!("> {}", query), width)))?;
+
+    let preview = filtered
+        .get(selected)
+        .and_then(|&i| previews.get(i))
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    // Split the screen between the list and the preview, keeping at least a few list rows
+    let preview_rows = preview.len().min(height.saturating_sub(8));
+    let list_rows = height.saturating_sub(4 + preview_rows).max(1);
+
+    // Scroll so the selection stays visible
+    let first = selected.saturating_sub(list_rows - 1);
+    for (row, &item_index) in filtered.iter().skip(first).take(list_rows).enumerate() {
+        queue!(out, MoveTo(0, (row + 2) as u16))?;
+        let line = truncate(&format!("  {}", items[item_index]), width);
+        if first + row == selected {
+            queue!(out, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset))?;
+        } else {
+            queue!(out, Print(line))?;
+        }
+    }
+
+    if preview_rows > 0 {
+        let top = height - preview_rows - 1;
+        queue!(out, MoveTo(0, top as u16), Print("─".repeat(width)))?;
+        for (row, line) in preview.iter().take(preview_rows).enumerate() {
+            queue!(out, MoveTo(0, (top + 1 + row) as u16), Print(truncate(line, width)))?;
+        }
+    }
+
+    out.flush()
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Shows a fuzzy-filterable list until an item is picked, the user cancels or `timeout` passes.
+/// `previews[i]` is shown below the list while item `i` is highlighted.
+pub fn choose(prompt: &str, items: &[String], previews: &[Vec<String>], timeout: Option<Duration>) -> io::Result<Choice> {
+    let _guard = TerminalGuard::enter()?;
+    let mut out = io::stdout();
+
+    let mut query = String::new();
+    let mut selected = 0;
+    let mut filtered = filter_items(items, &query);
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+        draw(&mut out, prompt, &query, items, previews, &filtered, selected)?;
+
+        if let Some(deadline) = deadline
+            && !event::poll(deadline.saturating_duration_since(Instant::now()))?
+        {
+            return Ok(Choice::TimedOut);
+        }
+
+        let Event::Key(KeyEvent { code, modifiers, kind, .. }) = event::read()? else {
+            continue;
+        };
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+        match code {
+            KeyCode::Esc => return Ok(Choice::Cancelled),
+            KeyCode::Char('c') | KeyCode::Char('g') if ctrl => return Ok(Choice::Cancelled),
+            KeyCode::Enter => {
+                return Ok(filtered.get(selected).map_or(Choice::Cancelled, |&index| Choice::Selected(index)));
+            }
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') | KeyCode::Char('k') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => selected = (selected + 1).min(filtered.len().saturating_sub(1)),
+            KeyCode::Char('n') | KeyCode::Char('j') if ctrl => {
+                selected = (selected + 1).min(filtered.len().saturating_sub(1))
+            }
+            KeyCode::Char('u') if ctrl => {
+                query.clear();
+                filtered = filter_items(items, &query);
+                selected = 0;
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                filtered = filter_items(items, &query);
+                selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                filtered = filter_items(items, &query);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("wrk", "Work").is_some());
+        assert!(fuzzy_score("xyz", "Work").is_none());
+        assert!(fuzzy_score("wo", "Work").unwrap() > fuzzy_score("wo", "Always open w.org in Personal").unwrap());
+    }
+
+    #[test]
+    fn test_filter_items_ranks_best_match_first() {
+        let items = vec![
+            "Personal — private window".to_string(),
+            "Work".to_string(),
+            "Personal".to_string(),
+        ];
+        assert_eq!(filter_items(&items, ""), vec![0, 1, 2]);
+        assert_eq!(filter_items(&items, "pers")[0], 2);
+        assert_eq!(filter_items(&items, "priv"), vec![0]);
+    }
+}
@@ -0,0 +1,118 @@
+use regex::Regex;
+use serde::Deserialize;
+use url::Url;
+
+// Guards against redirectors that (accidentally or not) point at each other
+const MAX_UNWRAP_DEPTH: usize = 10;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnwrapRule {
+    /// Regex matched against the full redirector URL
+    pub pattern: String,
+    /// Query parameter holding the embedded target URL
+    pub param: String,
+}
+
+impl UnwrapRule {
+    fn new(pattern: &str, param: &str) -> Self {
+        UnwrapRule {
+            pattern: pattern.to_string(),
+            param: param.to_string(),
+        }
+    }
+}
+
+pub fn builtin_rules() -> Vec<UnwrapRule> {
+    vec![
+        // Outlook / Microsoft Defender SafeLinks
+        UnwrapRule::new(r"^https?://[^/]*\.safelinks\.protection\.outlook\.com/", "url"),
+        // Google search result and Gmail redirects
+        UnwrapRule::new(r"^https?://(www\.)?google\.[a-z.]+/url\?", "q"),
+        UnwrapRule::new(r"^https?://(www\.)?google\.[a-z.]+/url\?", "url"),
+        // Slack link redirector
+        UnwrapRule::new(r"^https?://slack-redir\.net/link\?", "url"),
+        // Microsoft Teams safelinks wrapper
+        UnwrapRule::new(r"^https?://statics\.teams\.cdn\.office\.net/evergreen-assets/safelinks/", "url"),
+    ]
+}
+
+fn extract_target(url: &str, rules: &[UnwrapRule]) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+
+    for rule in rules {
+        let Ok(re) = Regex::new(&rule.pattern) else {
+            continue;
+        };
+        if !re.is_match(url) {
+            continue;
+        }
+        let target = parsed
+            .query_pairs()
+            .find(|(key, _)| key == rule.param.as_str())
+            .map(|(_, value)| value.into_owned());
+        if let Some(target) = target.filter(|t| t.contains("://")) {
+            return Some(target);
+        }
+    }
+    None
+}
+
+/// Follows redirector rules until the URL no longer matches any of them.
+/// User rules are tried before the built-in ones.
+pub fn unwrap_url(url: &str, custom_rules: &[UnwrapRule], use_builtin: bool, verbose: bool) -> String {
+    let mut rules = custom_rules.to_vec();
+    if use_builtin {
+        rules.extend(builtin_rules());
+    }
+
+    let mut current = url.to_string();
+    for _ in 0..MAX_UNWRAP_DEPTH {
+        match extract_target(&current, &rules) {
+            Some(target) => {
+                if verbose {
+                    println!("🔗 Unwrapped redirect: {} → {}", current, target);
+                }
+                current = target;
+            }
+            None => break,
+        }
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_safelinks() {
+        let url = "https://eur01.safelinks.protection.outlook.com/?url=https%3A%2F%2Fcompany.atlassian.net%2Fbrowse%2FABC-1&data=05";
+        assert_eq!(unwrap_url(url, &[], true, false), "https://company.atlassian.net/browse/ABC-1");
+    }
+
+    #[test]
+    fn test_unwrap_nested_redirects() {
+        let inner = "https://slack-redir.net/link?url=https%3A%2F%2Fgithub.com%2Fcompany";
+        let outer = format!(
+            "https://www.google.com/url?q={}&sa=D",
+            url::form_urlencoded::byte_serialize(inner.as_bytes()).collect::<String>()
+        );
+        assert_eq!(unwrap_url(&outer, &[], true, false), "https://github.com/company");
+    }
+
+    #[test]
+    fn test_custom_rule_and_builtin_toggle() {
+        let rules = vec![UnwrapRule::new(r"^https://links\.example\.com/", "target")];
+        let url = "https://links.example.com/c?target=https%3A%2F%2Freddit.com";
+        assert_eq!(unwrap_url(url, &rules, false, false), "https://reddit.com");
+
+        let safelink = "https://nam02.safelinks.protection.outlook.com/?url=https%3A%2F%2Freddit.com";
+        assert_eq!(unwrap_url(safelink, &[], false, false), safelink);
+    }
+
+    #[test]
+    fn test_plain_url_is_untouched() {
+        let url = "https://www.google.com/search?q=https://example.com";
+        assert_eq!(unwrap_url(url, &[], true, false), url);
+    }
+}